@@ -9,34 +9,46 @@ use rand::{Rand, thread_rng};
 use pairing::bn256::{Bn256, Fr};
 use poseidon_hash::group_hash::{BlakeHasher};
 use poseidon_hash::poseidon_hash;
+use poseidon_hash::batch::poseidon_hash_many;
 use poseidon_hash::bn256::Bn256PoseidonParams;
-use poseidon_hash::specialization::specialization_macro::PosendonR2C1;
-
-// #[bench]
-// fn bench_rate_2(b: &mut test::Bencher) {
-//     let params = Bn256PoseidonParams::new_checked_2_into_1();
-//     let rng = &mut thread_rng();
-//     let input = (0..2).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
-
-//     b.iter(|| {
-//         poseidon_hash::<Bn256>(&params, &input)
-//     });
-// }
-
-// #[bench]
-// fn bench_rate_specialized_2(b: &mut test::Bencher) {
-//     let params = Bn256PoseidonParams::new_checked_2_into_1();
-//     let rng = &mut thread_rng();
-//     let input = (0..2).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
-
-//     b.iter(|| {
-//         let mut p = PosendonR2C1::<Bn256>::new(&params);
-//         for &i in input.iter() {
-//             p.absorb_single_value(i);
-//         }
-//         p.squeeze_out_single()
-//     });
-// }
+use poseidon_hash::specialization::{PosendonR2C1, Compress2Into1};
+
+#[bench]
+fn bench_rate_2(b: &mut test::Bencher) {
+    let params = Bn256PoseidonParams::new_checked_2_into_1();
+    let rng = &mut thread_rng();
+    let input = (0..2).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+
+    b.iter(|| {
+        poseidon_hash::<Bn256>(&params, &input)
+    });
+}
+
+#[bench]
+fn bench_rate_specialized_2(b: &mut test::Bencher) {
+    let params = Bn256PoseidonParams::new_checked_2_into_1();
+    let rng = &mut thread_rng();
+    let input = (0..2).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+
+    b.iter(|| {
+        let mut p = PosendonR2C1::<Bn256>::new(&params);
+        p.absorb(&input);
+        p.squeeze_out_single()
+    });
+}
+
+#[bench]
+fn bench_compress_2_into_1(b: &mut test::Bencher) {
+    let params = Bn256PoseidonParams::new_checked_2_into_1();
+    let compressor = Compress2Into1::<Bn256>::new(&params);
+    let rng = &mut thread_rng();
+    let left = Fr::rand(rng);
+    let right = Fr::rand(rng);
+
+    b.iter(|| {
+        compressor.compress(left, right)
+    });
+}
 
 #[bench]
 fn bench_rate_2_80_bits(b: &mut test::Bencher) {
@@ -149,4 +161,16 @@ fn bench_rate_8_256_bits(b: &mut test::Bencher) {
     b.iter(|| {
         poseidon_hash::<Bn256>(&params, &input)
     });
-}
\ No newline at end of file
+}
+
+#[bench]
+fn bench_hash_many_rate_2(b: &mut test::Bencher) {
+    let params = Bn256PoseidonParams::new_checked_2_into_1();
+    let rng = &mut thread_rng();
+    let input = (0..2048).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let mut output = vec![Fr::rand(rng); 1024];
+
+    b.iter(|| {
+        poseidon_hash_many::<Bn256>(&params, &input, 2, &mut output)
+    });
+}
@@ -0,0 +1,331 @@
+use std::{fmt, ops};
+
+use crate::pairing::ff::{Field, PrimeField};
+use super::{PoseidonEngine, PoseidonHashParams, RoundConstantsPosition, SBox, small_sbox_exponent};
+
+/// Symbolic expression over the current and the next row of the trace
+/// and the periodic columns
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AirExpr<F: PrimeField> {
+    Constant(F),
+    Current(usize),
+    Next(usize),
+    Periodic(usize),
+    Add(Box<AirExpr<F>>, Box<AirExpr<F>>),
+    Sub(Box<AirExpr<F>>, Box<AirExpr<F>>),
+    Mul(Box<AirExpr<F>>, Box<AirExpr<F>>),
+    Pow(Box<AirExpr<F>>, u64),
+}
+
+impl<F: PrimeField> AirExpr<F> {
+    pub fn pow(self, exponent: u64) -> Self {
+        AirExpr::Pow(Box::new(self), exponent)
+    }
+
+    /// Degree in the trace columns. Periodic columns are treated as
+    /// constants, as they are interpolated separately by the prover
+    pub fn degree(&self) -> u64 {
+        match self {
+            AirExpr::Constant(_) | AirExpr::Periodic(_) => 0,
+            AirExpr::Current(_) | AirExpr::Next(_) => 1,
+            AirExpr::Add(a, b) | AirExpr::Sub(a, b) => a.degree().max(b.degree()),
+            AirExpr::Mul(a, b) => a.degree() + b.degree(),
+            AirExpr::Pow(a, e) => a.degree() * e,
+        }
+    }
+
+    pub fn evaluate(&self, current: &[F], next: &[F], periodic: &[F]) -> F {
+        match self {
+            AirExpr::Constant(c) => *c,
+            AirExpr::Current(i) => current[*i],
+            AirExpr::Next(i) => next[*i],
+            AirExpr::Periodic(i) => periodic[*i],
+            AirExpr::Add(a, b) => {
+                let mut result = a.evaluate(current, next, periodic);
+                result.add_assign(&b.evaluate(current, next, periodic));
+                result
+            },
+            AirExpr::Sub(a, b) => {
+                let mut result = a.evaluate(current, next, periodic);
+                result.sub_assign(&b.evaluate(current, next, periodic));
+                result
+            },
+            AirExpr::Mul(a, b) => {
+                let mut result = a.evaluate(current, next, periodic);
+                result.mul_assign(&b.evaluate(current, next, periodic));
+                result
+            },
+            AirExpr::Pow(a, e) => a.evaluate(current, next, periodic).pow([*e]),
+        }
+    }
+}
+
+impl<F: PrimeField> ops::Add for AirExpr<F> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        AirExpr::Add(Box::new(self), Box::new(other))
+    }
+}
+
+impl<F: PrimeField> ops::Sub for AirExpr<F> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        AirExpr::Sub(Box::new(self), Box::new(other))
+    }
+}
+
+impl<F: PrimeField> ops::Mul for AirExpr<F> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        AirExpr::Mul(Box::new(self), Box::new(other))
+    }
+}
+
+impl<F: PrimeField> fmt::Display for AirExpr<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AirExpr::Constant(c) => write!(f, "{}", c.into_repr()),
+            AirExpr::Current(i) => write!(f, "s{}", i),
+            AirExpr::Next(i) => write!(f, "s{}'", i),
+            AirExpr::Periodic(i) => write!(f, "p{}", i),
+            AirExpr::Add(a, b) => write!(f, "({} + {})", a, b),
+            AirExpr::Sub(a, b) => write!(f, "({} - {})", a, b),
+            AirExpr::Mul(a, b) => write!(f, "{} * {}", a, b),
+            AirExpr::Pow(a, e) => write!(f, "{}^{}", a, e),
+        }
+    }
+}
+
+// layout of the periodic columns
+pub const FULL_ROUND_SELECTOR: usize = 0;
+pub const PARTIAL_ROUND_SELECTOR: usize = 1;
+pub const ROUND_CONSTANTS_OFFSET: usize = 2;
+
+/// Poseidon permutation as an AIR. The trace has one column per state element
+/// and one row per round: row `i` is the state before round `i` and row `num_rounds`
+/// is the output. The trace is padded to a power of two by repeating the output
+/// and padding rows have both round selectors set to zero. Periodic columns are
+/// the full and partial round selectors followed by the round constants.
+pub struct PoseidonAir<'a, E: PoseidonEngine> {
+    params: &'a E::Params,
+    alpha: u64,
+    trace_length: usize,
+}
+
+impl<'a, E: PoseidonEngine> Clone for PoseidonAir<'a, E> {
+    fn clone(&self) -> Self {
+        Self {
+            params: self.params,
+            alpha: self.alpha,
+            trace_length: self.trace_length
+        }
+    }
+}
+
+impl<'a, E: PoseidonEngine> PoseidonAir<'a, E> {
+    pub fn new(
+        params: &'a E::Params
+    ) -> Self {
+        assert!(params.num_full_rounds() % 2 == 0);
+        assert!(params.round_constants_position() == RoundConstantsPosition::BeforeSBox, "round constants must be added before the sbox");
+        let alpha = small_sbox_exponent::<E, _>(params.sbox()).expect("sbox must be a low degree power map");
+        let num_rounds = (params.num_full_rounds() + params.num_partial_rounds()) as usize;
+
+        Self {
+            params,
+            alpha,
+            trace_length: (num_rounds + 1).next_power_of_two()
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.params.state_width() as usize
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        (self.params.num_full_rounds() + self.params.num_partial_rounds()) as usize
+    }
+
+    pub fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn is_full_round(&self, round: usize) -> bool {
+        let half_of_full_rounds = (self.params.num_full_rounds() / 2) as usize;
+        round < half_of_full_rounds || round >= half_of_full_rounds + self.params.num_partial_rounds() as usize
+    }
+
+    /// Trace columns for one permutation of `input`
+    pub fn generate_trace(&self, input: &[E::Fr]) -> Vec<Vec<E::Fr>> {
+        let width = self.width();
+        assert_eq!(input.len(), width);
+
+        let mut rows = Vec::with_capacity(self.trace_length);
+        let mut state = input.to_vec();
+        let mut scratch = vec![E::Fr::zero(); width];
+        rows.push(state.clone());
+        for round in 0..self.num_rounds() {
+            for (s, c) in state.iter_mut().zip(self.params.round_constants(round as u32).iter()) {
+                s.add_assign(c);
+            }
+            if self.is_full_round(round) {
+                self.params.sbox().apply(&mut state);
+            } else {
+                let sbox_idx = self.params.partial_round_sbox_index() as usize;
+                self.params.sbox().apply(&mut state[sbox_idx..(sbox_idx + 1)]);
+            }
+            for (row, place_into) in scratch.iter_mut().enumerate() {
+                *place_into = super::scalar_product::<E>(&state, self.params.mds_matrix_row(row as u32));
+            }
+            state.copy_from_slice(&scratch);
+            rows.push(state.clone());
+        }
+        while rows.len() < self.trace_length {
+            rows.push(state.clone());
+        }
+
+        (0..width).map(|column| rows.iter().map(|row| row[column]).collect()).collect()
+    }
+
+    #[allow(clippy::needless_range_loop)]
+    pub fn periodic_columns(&self) -> Vec<Vec<E::Fr>> {
+        let width = self.width();
+        let mut columns = vec![vec![E::Fr::zero(); self.trace_length]; ROUND_CONSTANTS_OFFSET + width];
+        for round in 0..self.num_rounds() {
+            let selector = if self.is_full_round(round) {
+                FULL_ROUND_SELECTOR
+            } else {
+                PARTIAL_ROUND_SELECTOR
+            };
+            columns[selector][round] = E::Fr::one();
+
+            for (i, c) in self.params.round_constants(round as u32).iter().enumerate() {
+                columns[ROUND_CONSTANTS_OFFSET + i][round] = *c;
+            }
+        }
+
+        columns
+    }
+
+    /// One constraint per state element:
+    /// `(full + partial) * s_j' - sum_k M_jk * sbox_k(s_k + c_k)`
+    /// where the sbox is applied to every element in full rounds and only
+    /// to the last one in partial rounds
+    pub fn transition_constraints(&self) -> Vec<AirExpr<E::Fr>> {
+        let width = self.width();
+        let full = || AirExpr::Periodic(FULL_ROUND_SELECTOR);
+        let partial = || AirExpr::Periodic(PARTIAL_ROUND_SELECTOR);
+        let active = || full() + partial();
+
+        let after_sbox: Vec<_> = (0..width).map(|k| {
+            let x = || AirExpr::Current(k) + AirExpr::Periodic(ROUND_CONSTANTS_OFFSET + k);
+            if k == self.params.partial_round_sbox_index() as usize {
+                active() * x().pow(self.alpha)
+            } else {
+                full() * x().pow(self.alpha) + partial() * x()
+            }
+        }).collect();
+
+        (0..width).map(|j| {
+            let mds_row = self.params.mds_matrix_row(j as u32);
+            let linear = after_sbox.iter().zip(mds_row.iter())
+                .map(|(s, m)| AirExpr::Constant(*m) * s.clone())
+                .reduce(|a, b| a + b)
+                .unwrap();
+
+            active() * AirExpr::Next(j) - linear
+        }).collect()
+    }
+
+    /// `(row, column, value)` assertions for the input and the output
+    pub fn boundary_constraints(&self, input: &[E::Fr], output: &[E::Fr]) -> Vec<(usize, usize, E::Fr)> {
+        let mut constraints = vec![];
+        for (column, value) in input.iter().enumerate() {
+            constraints.push((0, column, *value));
+        }
+        for (column, value) in output.iter().enumerate() {
+            constraints.push((self.num_rounds(), column, *value));
+        }
+
+        constraints
+    }
+
+    /// Returns the first row and constraint index that doesn't hold
+    pub fn find_violation(&self, trace: &[Vec<E::Fr>]) -> Option<(usize, usize)> {
+        assert_eq!(trace.len(), self.width());
+        let constraints = self.transition_constraints();
+        let periodic = self.periodic_columns();
+
+        for row in 0..(self.trace_length - 1) {
+            let current: Vec<_> = trace.iter().map(|column| column[row]).collect();
+            let next: Vec<_> = trace.iter().map(|column| column[row + 1]).collect();
+            let periodic: Vec<_> = periodic.iter().map(|column| column[row]).collect();
+
+            for (i, constraint) in constraints.iter().enumerate() {
+                if !constraint.evaluate(&current, &next, &periodic).is_zero() {
+                    return Some((row, i));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn is_satisfied(&self, trace: &[Vec<E::Fr>], input: &[E::Fr], output: &[E::Fr]) -> bool {
+        let boundary_holds = self.boundary_constraints(input, output).into_iter()
+            .all(|(row, column, value)| trace[column][row] == value);
+
+        boundary_holds && self.find_violation(trace).is_none()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::poseidon_mimc;
+    use super::*;
+
+    #[test]
+    fn test_bn256_air_trace_is_valid() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let air = PoseidonAir::<Bn256>::new(&params);
+        let input: Vec<Fr> = (0..3).map(|_| rng.gen()).collect();
+        let output = poseidon_mimc::<Bn256>(&params, &input);
+
+        let trace = air.generate_trace(&input);
+        assert_eq!(trace[0].len(), air.trace_length());
+        assert!(air.trace_length().is_power_of_two());
+        assert!(air.is_satisfied(&trace, &input, &output));
+
+        for constraint in air.transition_constraints() {
+            assert_eq!(constraint.degree(), 5);
+        }
+    }
+
+    #[test]
+    fn test_bn256_air_detects_invalid_trace() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let air = PoseidonAir::<Bn256>::new(&params);
+        let input: Vec<Fr> = (0..3).map(|_| rng.gen()).collect();
+        let output = poseidon_mimc::<Bn256>(&params, &input);
+
+        // in the middle of partial rounds
+        let mut trace = air.generate_trace(&input);
+        trace[1][30].add_assign(&Fr::one());
+        assert_eq!(air.find_violation(&trace), Some((29, 1)));
+
+        // padding rows are not constrained, but the output is
+        let mut trace = air.generate_trace(&input);
+        let last = air.num_rounds();
+        trace[0][last].add_assign(&Fr::one());
+        assert_eq!(air.find_violation(&trace), Some((last - 1, 0)));
+        assert!(!air.is_satisfied(&trace, &input, &output));
+    }
+}
@@ -18,10 +18,6 @@ use mathru::algebra::abstr::{
     cast::{ToPrimitive, FromPrimitive, NumCast},
 };
 
-use mathru::elementary::{
-    Power
-};
-
 impl<F: PrimeField> Ring for Element<F> {}
 
 impl<F: PrimeField> CommutativeRing for Element<F> {}
@@ -144,6 +140,7 @@ impl<F: PrimeField> Scalar for Element<F> {
 
 
 #[cfg(test)]
+#[allow(unused_imports)]
 mod test {
     use super::*;
 
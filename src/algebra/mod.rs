@@ -19,6 +19,8 @@ mod additive_impls;
 mod num_impls;
 mod mathru;
 
+// only constructed by the tests of the mathru bindings
+#[cfg_attr(not(test), allow(dead_code))]
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Element<F: PrimeField>(F);
 
@@ -30,7 +32,7 @@ impl<F: PrimeField> std::fmt::Display for Element<F> {
 
 impl<F: PrimeField> std::cmp::PartialOrd for Element<F> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.0.into_repr().cmp(&other.0.into_repr()))
+        Some(self.cmp(other))
     }
 }
 
@@ -72,6 +74,7 @@ impl<F: PrimeField> AbstractField<Additive, Multiplicative> for Element<F> {}
 
 
 #[cfg(test)]
+#[allow(unused_imports)]
 mod test {
     use super::*;
 
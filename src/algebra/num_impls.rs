@@ -21,11 +21,6 @@ use std::ops::{
 use num_traits::{
     Zero,
     One,
-    NumOps,
-    Num,
-    NumAssignOps,
-    FromPrimitive,
-    ToPrimitive
 };
 
 impl<F: PrimeField> Add for Element<F> {
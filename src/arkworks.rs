@@ -0,0 +1,293 @@
+use std::any::TypeId;
+use std::fmt;
+
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ff::{BigInteger, PrimeField as ArkPrimeField};
+
+use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+use crate::pairing::{bls12_381, bn256};
+use crate::bn256::Bn256PoseidonParams;
+use crate::sampling::ParamsSeeds;
+use super::{ParamsError, PoseidonEngine, PoseidonHashParams, QuinticSBox, RoundConstantsPosition, StatefulSponge, check_security_level, check_shape, small_sbox_exponent};
+
+/// Engines with a matching arkworks field
+pub trait ArkworksEngine: PoseidonEngine {
+    type ArkFr: ArkPrimeField;
+}
+
+impl ArkworksEngine for bn256::Bn256 {
+    type ArkFr = ark_bn254::Fr;
+}
+
+impl ArkworksEngine for bls12_381::Bls12 {
+    type ArkFr = ark_bls12_381::Fr;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArkworksConversionError {
+    /// arkworks only supports `x^alpha` sboxes
+    UnsupportedSBox,
+    /// params with round constants after the MDS can't be expressed
+    UnsupportedRoundConstantsPosition,
+    /// arkworks applies the sbox of partial rounds to the first capacity element
+    PartialRoundSBoxIndex { expected: u32, actual: u32 },
+}
+
+impl fmt::Display for ArkworksConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArkworksConversionError::UnsupportedSBox => write!(f, "sbox is not a power map"),
+            ArkworksConversionError::UnsupportedRoundConstantsPosition => write!(f, "round constants must be added before the sbox"),
+            ArkworksConversionError::PartialRoundSBoxIndex { expected, actual } => {
+                write!(f, "sbox of partial rounds must be applied to element {}, params use {}", expected, actual)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ArkworksConversionError {}
+
+/// Converts through the canonical little endian encoding
+pub fn to_ark_field<E: ArkworksEngine>(element: &E::Fr) -> E::ArkFr {
+    let mut bytes = vec![];
+    element.into_repr().write_le(&mut bytes).unwrap();
+
+    // encoding is canonical, so nothing is actually reduced
+    E::ArkFr::from_le_bytes_mod_order(&bytes)
+}
+
+pub fn from_ark_field<E: ArkworksEngine>(element: &E::ArkFr) -> E::Fr {
+    let bytes = element.into_bigint().to_bytes_le();
+    let mut repr = <E::Fr as PrimeField>::Repr::default();
+    repr.read_le(&bytes[..]).expect("encodings have the same length");
+
+    E::Fr::from_repr(repr).expect("fields have the same characteristic")
+}
+
+// arkworks keeps the capacity in front of the rate, so element `k` of
+// arkworks' state is element `(k + rate) % t` of ours
+fn ark_index(k: usize, rate: usize, t: usize) -> usize {
+    (k + rate) % t
+}
+
+/// Config for arkworks' `PoseidonSponge` with the same permutation and sponge
+pub fn to_ark_config<E: ArkworksEngine>(params: &E::Params) -> Result<PoseidonConfig<E::ArkFr>, ArkworksConversionError> {
+    let alpha = small_sbox_exponent::<E, _>(params.sbox()).ok_or(ArkworksConversionError::UnsupportedSBox)?;
+    if params.round_constants_position() != RoundConstantsPosition::BeforeSBox {
+        return Err(ArkworksConversionError::UnsupportedRoundConstantsPosition);
+    }
+    if params.partial_round_sbox_index() != params.rate() {
+        return Err(ArkworksConversionError::PartialRoundSBoxIndex { expected: params.rate(), actual: params.partial_round_sbox_index() });
+    }
+
+    let t = params.state_width() as usize;
+    let rate = params.rate() as usize;
+    let num_rounds = params.num_full_rounds() + params.num_partial_rounds();
+
+    let ark = (0..num_rounds).map(|round| {
+        let constants = params.round_constants(round);
+        (0..t).map(|k| to_ark_field::<E>(&constants[ark_index(k, rate, t)])).collect()
+    }).collect();
+    let mds = (0..t).map(|i| {
+        let row = params.mds_matrix_row(ark_index(i, rate, t) as u32);
+        (0..t).map(|j| to_ark_field::<E>(&row[ark_index(j, rate, t)])).collect()
+    }).collect();
+
+    Ok(PoseidonConfig::new(
+        params.num_full_rounds() as usize,
+        params.num_partial_rounds() as usize,
+        alpha,
+        mds,
+        ark,
+        rate,
+        params.capacity() as usize,
+    ))
+}
+
+impl Bn256PoseidonParams {
+    /// Params from an arkworks config with `x^5` sbox. arkworks configs don't
+    /// record how they were generated, so the params have default seeds
+    /// and `"arkworks"` as the hasher name, and the security level is
+    /// up to the caller
+    pub fn from_ark_config(config: &PoseidonConfig<ark_bn254::Fr>, security_level: u32) -> Result<Self, ParamsError> {
+        let capacity = config.capacity as u32;
+        // output length of the bn256 params is the capacity
+        check_shape(config.rate as u32, capacity, config.full_rounds as u32, config.partial_rounds as u32, capacity)?;
+        check_security_level::<bn256::Fr>(capacity, security_level)?;
+        if config.alpha != 5 {
+            return Err(ParamsError::UnsupportedAlpha(config.alpha));
+        }
+        let t = config.rate + config.capacity;
+        let malformed = config.ark.len() != config.full_rounds + config.partial_rounds
+            || config.ark.iter().any(|round| round.len() != t)
+            || config.mds.len() != t
+            || config.mds.iter().any(|row| row.len() != t);
+        if malformed {
+            return Err(ParamsError::MalformedConstants);
+        }
+
+        // inverse of `ark_index`
+        let index = |i: usize| (i + config.capacity) % t;
+        let round_constants = config.ark.iter()
+            .flat_map(|round| (0..t).map(move |i| from_ark_field::<bn256::Bn256>(&round[index(i)])))
+            .collect();
+        let mds_matrix = (0..t)
+            .flat_map(|i| (0..t).map(move |j| from_ark_field::<bn256::Bn256>(&config.mds[index(i)][index(j)])))
+            .collect();
+
+        Ok(Self {
+            c: config.capacity as u32,
+            r: config.rate as u32,
+            full_rounds: config.full_rounds as u32,
+            partial_rounds: config.partial_rounds as u32,
+            round_constants,
+            mds_matrix,
+            security_level,
+            sbox: QuinticSBox { _marker: std::marker::PhantomData },
+            seeds: ParamsSeeds::default(),
+            hasher: "arkworks",
+        })
+    }
+}
+
+/// Digests match arkworks' `PoseidonSponge` with the config from `to_ark_config`
+/// when everything is absorbed before squeezing and the absorbed length is
+/// a multiple of the rate, otherwise padding of `StatefulSponge` applies.
+/// arkworks 0.4 skips a permutation when a squeeze starts in the middle of
+/// the rate and crosses it, so only squeezes aligned to the rate agree
+impl<'a, E: ArkworksEngine> CryptographicSponge for StatefulSponge<'a, E> {
+    type Config = &'a E::Params;
+
+    fn new(params: &Self::Config) -> Self {
+        StatefulSponge::new(*params)
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        for element in input.to_sponge_field_elements_as_vec::<E::ArkFr>() {
+            self.absorb_single_value(from_ark_field::<E>(&element));
+        }
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        let usable_bytes = ((E::ArkFr::MODULUS_BIT_SIZE - 1) / 8) as usize;
+        let num_elements = num_bytes.div_ceil(usable_bytes);
+
+        let mut bytes = Vec::with_capacity(usable_bytes * num_elements);
+        for element in self.squeeze_native_field_elements(num_elements) {
+            bytes.extend_from_slice(&element.into_bigint().to_bytes_le()[..usable_bytes]);
+        }
+        bytes.truncate(num_bytes);
+
+        bytes
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        let usable_bits = (E::ArkFr::MODULUS_BIT_SIZE - 1) as usize;
+        let num_elements = num_bits.div_ceil(usable_bits);
+
+        let mut bits = Vec::with_capacity(usable_bits * num_elements);
+        for element in self.squeeze_native_field_elements(num_elements) {
+            bits.extend_from_slice(&element.into_bigint().to_bits_le()[..usable_bits]);
+        }
+        bits.truncate(num_bits);
+
+        bits
+    }
+
+    fn squeeze_field_elements<F: ArkPrimeField>(&mut self, num_elements: usize) -> Vec<F> {
+        if TypeId::of::<F>() == TypeId::of::<E::ArkFr>() {
+            self.squeeze_native_field_elements(num_elements)
+                .iter()
+                .map(|element| F::from_le_bytes_mod_order(&element.into_bigint().to_bytes_le()))
+                .collect()
+        } else {
+            self.squeeze_field_elements_with_sizes::<F>(
+                &vec![ark_crypto_primitives::sponge::FieldElementSize::Full; num_elements]
+            )
+        }
+    }
+}
+
+impl<'a, E: ArkworksEngine> FieldBasedCryptographicSponge<E::ArkFr> for StatefulSponge<'a, E> {
+    fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Vec<E::ArkFr> {
+        (0..num_elements).map(|_| to_ark_field::<E>(&self.squeeze_out_single())).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_crypto_primitives::sponge::poseidon::{PoseidonSponge, find_poseidon_ark_and_mds};
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::poseidon_hash;
+    use super::*;
+
+    fn compare_sponges(params: &Bn256PoseidonParams, config: &PoseidonConfig<ark_bn254::Fr>) {
+        let rng = &mut thread_rng();
+        let input: Vec<ark_bn254::Fr> = (0..(2 * params.rate()))
+            .map(|_| to_ark_field::<Bn256>(&rng.gen::<Fr>()))
+            .collect();
+
+        let mut ours = <StatefulSponge<Bn256> as CryptographicSponge>::new(&params);
+        let mut theirs = PoseidonSponge::new(config);
+        // absorbing in pieces is the same as absorbing at once
+        CryptographicSponge::absorb(&mut ours, &input[..1].to_vec());
+        CryptographicSponge::absorb(&mut ours, &input[1..].to_vec());
+        theirs.absorb(&input);
+
+        let ours_elements: Vec<ark_bn254::Fr> = ours.squeeze_field_elements(4);
+        let theirs_elements: Vec<ark_bn254::Fr> = theirs.squeeze_field_elements(4);
+        assert_eq!(ours_elements, theirs_elements);
+        assert_eq!(ours.squeeze_bytes(40), theirs.squeeze_bytes(40));
+    }
+
+    #[test]
+    fn test_field_conversion() {
+        let rng = &mut thread_rng();
+        for _ in 0..10 {
+            let element: Fr = rng.gen();
+            assert_eq!(from_ark_field::<Bn256>(&to_ark_field::<Bn256>(&element)), element);
+        }
+        assert_eq!(to_ark_field::<Bn256>(&Fr::from_str("12345").unwrap()), ark_bn254::Fr::from(12345u64));
+    }
+
+    #[test]
+    fn test_sponge_matches_arkworks() {
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let config = to_ark_config::<Bn256>(&params).unwrap();
+        compare_sponges(&params, &config);
+
+        let roundtrip = Bn256PoseidonParams::from_ark_config(&config, 126).unwrap();
+        let input = [Fr::from_str("1").unwrap(), Fr::from_str("2").unwrap()];
+        assert_eq!(poseidon_hash::<Bn256>(&roundtrip, &input), poseidon_hash::<Bn256>(&params, &input));
+    }
+
+    #[test]
+    fn test_arkworks_generated_config() {
+        let (ark, mds) = find_poseidon_ark_and_mds::<ark_bn254::Fr>(254, 2, 8, 57, 0);
+        let config = PoseidonConfig::new(8, 57, 5, mds, ark, 2, 1);
+        let params = Bn256PoseidonParams::from_ark_config(&config, 126).unwrap();
+        compare_sponges(&params, &config);
+
+        let cubic = PoseidonConfig::new(8, 57, 3, config.mds.clone(), config.ark.clone(), 2, 1);
+        assert_eq!(Bn256PoseidonParams::from_ark_config(&cubic, 126).err(), Some(ParamsError::UnsupportedAlpha(3)));
+        assert_eq!(params.security_level(), 126);
+        assert_eq!(
+            Bn256PoseidonParams::from_ark_config(&config, 128).err(),
+            Some(ParamsError::InvalidSecurityLevel { security_level: 128, max: 126 })
+        );
+
+        // `PoseidonConfig::new` asserts the shape, but the fields are public
+        let mut odd = config.clone();
+        odd.full_rounds = 7;
+        odd.partial_rounds = 58;
+        assert_eq!(Bn256PoseidonParams::from_ark_config(&odd, 126).err(), Some(ParamsError::OddNumberOfFullRounds(7)));
+        let mut short_ark = config.clone();
+        short_ark.ark.pop();
+        assert_eq!(Bn256PoseidonParams::from_ark_config(&short_ark, 126).err(), Some(ParamsError::MalformedConstants));
+        let mut wide = config.clone();
+        wide.rate = 3;
+        assert_eq!(Bn256PoseidonParams::from_ark_config(&wide, 126).err(), Some(ParamsError::MalformedConstants));
+    }
+}
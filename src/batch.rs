@@ -0,0 +1,150 @@
+use crate::pairing::ff::Field;
+use super::{PoseidonEngine, PoseidonHashParams, poseidon_mimc_in_place};
+
+#[cfg(feature = "parallel")]
+use crate::rayon::prelude::*;
+
+/// Applies the permutation to every `state_width` sized chunk of `states`
+pub fn poseidon_mimc_many<E: PoseidonEngine>(
+    params: &E::Params,
+    states: &mut [E::Fr]
+) {
+    let width = params.state_width() as usize;
+    assert!(states.len() % width == 0, "states must be a multiple of the state width");
+
+    #[cfg(feature = "parallel")]
+    states.par_chunks_mut(width).for_each_init(
+        || vec![E::Fr::zero(); width],
+        |scratch, state| poseidon_mimc_in_place::<E>(params, state, scratch)
+    );
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut scratch = vec![E::Fr::zero(); width];
+        for state in states.chunks_mut(width) {
+            poseidon_mimc_in_place::<E>(params, state, &mut scratch);
+        }
+    }
+}
+
+/// Hashes `inputs.len() / input_len` independent inputs of `input_len` elements each
+/// and places `params.capacity()` elements per input into `outputs`. Every output is
+/// equal to `poseidon_hash` of the corresponding input.
+pub fn poseidon_hash_many<E: PoseidonEngine>(
+    params: &E::Params,
+    inputs: &[E::Fr],
+    input_len: usize,
+    outputs: &mut [E::Fr]
+) {
+    assert!(input_len > 0, "input length must be non-zero");
+    assert!(inputs.len() % input_len == 0, "inputs must be a multiple of the input length");
+    let output_len = params.capacity() as usize;
+    assert_eq!(outputs.len(), inputs.len() / input_len * output_len, "output buffer has invalid length");
+
+    let width = params.state_width() as usize;
+
+    #[cfg(feature = "parallel")]
+    inputs.par_chunks(input_len).zip(outputs.par_chunks_mut(output_len)).for_each_init(
+        || (vec![E::Fr::zero(); width], vec![E::Fr::zero(); width]),
+        |(state, scratch), (input, output)| hash_into::<E>(params, input, output, state, scratch)
+    );
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut state = vec![E::Fr::zero(); width];
+        let mut scratch = vec![E::Fr::zero(); width];
+        for (input, output) in inputs.chunks(input_len).zip(outputs.chunks_mut(output_len)) {
+            hash_into::<E>(params, input, output, &mut state, &mut scratch);
+        }
+    }
+}
+
+// mirrors `StatefulSponge::absorb` followed by `squeeze_out_single` calls
+// without any intermediate allocations
+fn hash_into<E: PoseidonEngine>(
+    params: &E::Params,
+    input: &[E::Fr],
+    output: &mut [E::Fr],
+    state: &mut [E::Fr],
+    scratch: &mut [E::Fr]
+) {
+    let rate = params.rate() as usize;
+    for s in state.iter_mut() {
+        *s = E::Fr::zero();
+    }
+
+    let mut absorbtion_cycles = input.len() / rate;
+    if input.len() % rate != 0 || input.is_empty() {
+        absorbtion_cycles += 1;
+    }
+
+    for cycle in 0..absorbtion_cycles {
+        for (i, s) in state[..rate].iter_mut().enumerate() {
+            match input.get(cycle * rate + i) {
+                Some(value) => s.add_assign(value),
+                None => s.add_assign(&E::Fr::one())
+            }
+        }
+        poseidon_mimc_in_place::<E>(params, state, scratch);
+    }
+
+    for (i, out) in output.iter_mut().enumerate() {
+        if i != 0 && i % rate == 0 {
+            poseidon_mimc_in_place::<E>(params, state, scratch);
+        }
+        *out = state[i % rate];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::{poseidon_hash, poseidon_mimc};
+    use super::*;
+
+    #[test]
+    fn test_bn256_hash_many_matches_scalar() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+
+        for input_len in 1..6 {
+            let inputs: Vec<Fr> = (0..(input_len * 17)).map(|_| rng.gen()).collect();
+            let mut outputs = vec![Fr::zero(); 17];
+            poseidon_hash_many::<Bn256>(&params, &inputs, input_len, &mut outputs);
+
+            for (input, output) in inputs.chunks(input_len).zip(outputs.iter()) {
+                assert_eq!(poseidon_hash::<Bn256>(&params, input), vec![*output]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bn256_hash_many_wide_capacity() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_for_params::<BlakeHasher>(3, 2, 33, 8, 80);
+        let inputs: Vec<Fr> = (0..12).map(|_| rng.gen()).collect();
+        let mut outputs = vec![Fr::zero(); 12];
+        poseidon_hash_many::<Bn256>(&params, &inputs, 3, &mut outputs);
+
+        for (input, output) in inputs.chunks(3).zip(outputs.chunks(3)) {
+            assert_eq!(poseidon_hash::<Bn256>(&params, input), output.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_bn256_mimc_many_matches_scalar() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let states: Vec<Fr> = (0..30).map(|_| rng.gen()).collect();
+
+        let mut permuted = states.clone();
+        poseidon_mimc_many::<Bn256>(&params, &mut permuted);
+
+        for (state, permuted) in states.chunks(3).zip(permuted.chunks(3)) {
+            assert_eq!(poseidon_mimc::<Bn256>(&params, state), permuted.to_vec());
+        }
+    }
+}
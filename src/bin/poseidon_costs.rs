@@ -0,0 +1,52 @@
+extern crate pairing;
+extern crate poseidon_hash;
+
+use pairing::bn256::Bn256;
+use poseidon_hash::PoseidonHashParams;
+use poseidon_hash::bn256::Bn256PoseidonParams;
+use poseidon_hash::cost::{estimate_hash_cost, PlonkGateSet};
+use poseidon_hash::group_hash::BlakeHasher;
+
+// usage: poseidon_costs [input_len], by default every configuration
+// hashes a single full absorbtion cycle
+fn main() {
+    let input_len: Option<usize> = std::env::args().nth(1).map(|arg| arg.parse().expect("input length must be a number"));
+
+    // same configurations as in benches/hash.rs
+    let configurations = [
+        ("checked 2 into 1", Bn256PoseidonParams::new_checked_2_into_1()),
+        ("rate 2, 80 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 33, 8, 80)),
+        ("rate 4, 80 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 4, 35, 8, 80)),
+        ("rate 2, 128 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 57, 8, 80)),
+        ("rate 4, 128 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 4, 60, 8, 80)),
+        ("rate 8, 128 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 8, 63, 8, 80)),
+        ("rate 4, 256 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 4, 120, 8, 256)),
+        ("rate 8, 256 bits", Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 8, 120, 8, 256)),
+    ];
+
+    print!("{:<18} {:>4} {:>4} {:>5} {:>6} {:>6} {:>8}", "params", "t", "RP", "input", "perms", "r1cs", "native");
+    for gate_set in PlonkGateSet::ALL.iter() {
+        print!(" {:>9}", gate_set.name());
+    }
+    println!();
+
+    for (name, params) in configurations.iter() {
+        let input_len = input_len.unwrap_or(params.rate() as usize);
+        let cost = estimate_hash_cost::<Bn256>(params, input_len);
+
+        print!(
+            "{:<18} {:>4} {:>4} {:>5} {:>6} {:>6} {:>8}",
+            name,
+            params.state_width(),
+            params.num_partial_rounds(),
+            input_len,
+            cost.permutations,
+            cost.r1cs_constraints,
+            cost.native_multiplications,
+        );
+        for gate_set in PlonkGateSet::ALL.iter() {
+            print!(" {:>9}", cost.plonk_gates(*gate_set));
+        }
+        println!();
+    }
+}
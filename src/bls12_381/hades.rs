@@ -0,0 +1,227 @@
+use num_bigint::BigUint;
+use sha2::{Digest, Sha512};
+use crate::blake2_rfc::blake2b::Blake2b;
+use crate::pairing::bls12_381::{Bls12, Fr};
+use crate::pairing::ff::{Field, PrimeField};
+use crate::{biguint_to_repr, repr_to_biguint, poseidon_mimc, PoseidonHashParams, QuinticSBox};
+use super::Bls12PoseidonParams;
+use super::neptune::neptune_mds_matrix;
+
+/// Width of the Hades252 permutation
+pub const HADES_WIDTH: u32 = 5;
+
+const HADES_FULL_ROUNDS: u32 = 8;
+
+const HADES_PARTIAL_ROUNDS: u32 = 59;
+
+const HADES_SECURITY_LEVEL: u32 = 128;
+
+/// Domain separator of dusk-poseidon's `Domain::Other`, used for any input
+/// that is not a merkle tree level
+pub const HADES_DOMAIN_OTHER: u64 = 0;
+
+// reduces 64 little endian bytes modulo the characteristic, like
+// `BlsScalar::from_bytes_wide`
+fn wide_to_field(bytes: &[u8]) -> Fr {
+    let modulus = repr_to_biguint::<Fr>(&Fr::char());
+    let reduced = BigUint::from_bytes_le(bytes) % &modulus;
+
+    Fr::from_repr(biguint_to_repr::<Fr>(&reduced)).unwrap()
+}
+
+// dusk-hades stores its constants in Montgomery form and loads them with
+// `BlsScalar::from_raw`, so the permutation uses every constant times `2^256`
+fn montgomery_r() -> Fr {
+    let modulus = repr_to_biguint::<Fr>(&Fr::char());
+    let r = (BigUint::from(1u64) << 256) % modulus;
+
+    Fr::from_repr(biguint_to_repr::<Fr>(&r)).unwrap()
+}
+
+/// Constants of Hades252 are running sums `c_i = c_{i-1} + wide(h_i)` starting
+/// from one, where `h_0 = b"poseidon-for-plonk"` and `h_i = SHA-512(h_{i-1})`,
+/// as loaded by dusk-hades
+fn hades_round_constants(num_constants: usize) -> Vec<Fr> {
+    let r = montgomery_r();
+    let mut bytes = b"poseidon-for-plonk".to_vec();
+    let mut previous = Fr::one();
+    let mut constants = Vec::with_capacity(num_constants);
+    for _ in 0..num_constants {
+        bytes = Sha512::digest(&bytes).to_vec();
+
+        let mut constant = wide_to_field(&bytes);
+        constant.add_assign(&previous);
+        previous = constant;
+        constant.mul_assign(&r);
+        constants.push(constant);
+    }
+
+    constants
+}
+
+/// Cauchy matrix `1 / (i + j + 5)` of Hades252, as loaded by dusk-hades
+fn hades_mds_matrix() -> Vec<Fr> {
+    let r = montgomery_r();
+    let mut matrix = neptune_mds_matrix(HADES_WIDTH);
+    for element in matrix.iter_mut() {
+        element.mul_assign(&r);
+    }
+
+    matrix
+}
+
+impl Bls12PoseidonParams {
+    /// Params of the Hades252 permutation of dusk-network: `t = 5`, 8 full
+    /// and 59 partial rounds, the sbox of partial rounds is on the last element.
+    /// dusk-poseidon uses it up to 0.37, later versions have 60 partial rounds
+    pub fn new_hades252() -> Self {
+        let num_round_constants = HADES_WIDTH * (HADES_FULL_ROUNDS + HADES_PARTIAL_ROUNDS);
+
+        Self {
+            c: 1,
+            r: HADES_WIDTH - 1,
+            full_rounds: HADES_FULL_ROUNDS,
+            partial_rounds: HADES_PARTIAL_ROUNDS,
+            round_constants: hades_round_constants(num_round_constants as usize),
+            mds_matrix: hades_mds_matrix(),
+            security_level: HADES_SECURITY_LEVEL,
+            partial_round_sbox_index: HADES_WIDTH - 1,
+            sbox: QuinticSBox { _marker: std::marker::PhantomData },
+        }
+    }
+}
+
+/// Capacity element of dusk-safe's sponge absorbing `input_len` elements in
+/// one call and squeezing one: Blake2b-512 of the big endian words
+/// `2^31 + input_len`, `1` and the 64 bit `domain`, reduced into the field
+pub fn hades_tag(domain: u64, input_len: usize) -> Fr {
+    assert!(input_len > 0 && input_len < (1 << 31), "input length must be in 1..2^31");
+    let mut input = Vec::with_capacity(16);
+    input.extend_from_slice(&((1u32 << 31) + input_len as u32).to_be_bytes());
+    input.extend_from_slice(&1u32.to_be_bytes());
+    input.extend_from_slice(&domain.to_be_bytes());
+
+    let mut hasher = Blake2b::new(64);
+    hasher.update(&input);
+
+    wide_to_field(hasher.finalize().as_bytes())
+}
+
+/// Domain separator of the merkle tree hash of `arity` elements, `2^arity - 1`.
+/// dusk-poseidon defines it for arities 2 and 4
+pub fn hades_merkle_domain(arity: usize) -> u64 {
+    assert!(arity > 0 && arity < HADES_WIDTH as usize, "arity must be positive and below the width");
+
+    (1u64 << arity) - 1
+}
+
+/// dusk-poseidon's `Hash::digest(Domain::Other, messages)`
+pub fn hades_sponge_hash(params: &Bls12PoseidonParams, messages: &[Fr]) -> Fr {
+    hades_sponge_hash_with_tag(params, hades_tag(HADES_DOMAIN_OTHER, messages.len()), messages)
+}
+
+/// dusk-poseidon's merkle hash, `Hash::digest(Domain::Merkle{arity}, preimage)`
+pub fn hades_merkle_hash(params: &Bls12PoseidonParams, preimage: &[Fr]) -> Fr {
+    let tag = hades_tag(hades_merkle_domain(preimage.len()), preimage.len());
+
+    hades_sponge_hash_with_tag(params, tag, preimage)
+}
+
+/// dusk-safe's sponge with the capacity element set to `tag`: messages are
+/// added to the other four elements, the state is permuted before an element
+/// that does not fit and once more to squeeze the second element. There is
+/// no padding, the tag encodes the length of the input
+pub fn hades_sponge_hash_with_tag(params: &Bls12PoseidonParams, tag: Fr, messages: &[Fr]) -> Fr {
+    assert_eq!(params.state_width(), HADES_WIDTH);
+    let rate = (HADES_WIDTH - 1) as usize;
+
+    let mut state = vec![Fr::zero(); HADES_WIDTH as usize];
+    state[0] = tag;
+    for (i, chunk) in messages.chunks(rate).enumerate() {
+        if i > 0 {
+            state = poseidon_mimc::<Bls12>(params, &state);
+        }
+        for (s, m) in state[1..].iter_mut().zip(chunk.iter()) {
+            s.add_assign(m);
+        }
+    }
+
+    poseidon_mimc::<Bls12>(params, &state)[1]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::ff::from_hex;
+    use super::*;
+
+    // `TEST_INPUTS` of dusk-poseidon's sponge tests, as big endian hex
+    const DUSK_INPUTS: [&str; 10] = [
+        "25966cb78ae954c3f973dc091552554cd1c055de1e2eed0d49dbf15b26ed67bb",
+        "0760eeb1c3da2144b8e39be2b2915907b8b57bd435949ec8105ed7840022747e",
+        "08439d4f53e24f0e9fe30b30fba82a1d9f1bda6127f79834a0cc784d1a48e55c",
+        "0b25be01752c375031df7c8e9fd7eb98f4f64a4f4eceb05451c3a8c4e310e7b1",
+        "0633cc5287db8602cecb489bd0993dfc68a1208eabf14921d8b5865f02e2c933",
+        "35ea246eb29084e4f05556b394610025fcd497b9794014e5e491e7dcbf0682e9",
+        "2dccd81fc72b79dcb2e6df934e31dcffe5fe07a4d40bb25b8dde52c55ca9d286",
+        "15fa517dad7fd08ba17838c9bb0b72e1f18dc27b0ad26309c7a828ce0783dd4e",
+        "1eb2244098175a79fcd836537475b158d76d5fc8ad951fa08aa60467297abcea",
+        "3a1a707ba6154bc2e023a274e1e47a09b767c7b983c2315e30df933c6708c1cf",
+    ];
+
+    fn fr(value: u64) -> Fr {
+        Fr::from_str(&value.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_hades252_params() {
+        let params = Bls12PoseidonParams::new_hades252();
+        assert_eq!(params.state_width(), 5);
+        assert_eq!(params.round_constants.len(), 335);
+        assert_eq!(params.partial_round_sbox_index(), 4);
+        assert_eq!(params.mds_matrix_row(1)[2], from_hex("2000000000000000000000000000000000000000000000000000000000000000").unwrap());
+
+        // first and last used limbs of `assets/ark.bin` of dusk-hades
+        assert_eq!(params.round_constants[0], from_hex("6d67dfb07c22c6fd0b22407b580659556e7c8f8b712cab9e973d2bb834de71c5").unwrap());
+        assert_eq!(params.round_constants[334], from_hex("43c11ea111b702a481961dcf41e87432238a9f25097bb81a392605f2fa5796e1").unwrap());
+    }
+
+    #[test]
+    fn test_hades252_dusk_vectors() {
+        let params = Bls12PoseidonParams::new_hades252();
+
+        // dusk-poseidon's sponge vectors, hashed with a zero tag and a trailing one
+        let inputs: Vec<Fr> = DUSK_INPUTS.iter().map(|x| from_hex(x).unwrap()).collect();
+        let vectors = [
+            (3, "2885ca6d908b34ca83f2177d78283c25d8c5c7230877025bc8d558b8a94e6fe3"),
+            (4, "55f7f755570a884cb1430bf5cba11fff94430bea63f3c108e6070dc86532ea75"),
+            (5, "4288f8b92a9a8bc20f60aac68a318b3287ddb0a663cef5015bff0e98a0063153"),
+            (6, "31673ed327fa548518084e7332c29d4c96ad314cee79d2c447ff4f686458811a"),
+            (8, "5aa2df13cf6f910c19e5516c9222df7039d119472534e6488081df57d036b9a8"),
+            (10, "0be3889073101db27cd27006256daedda56368a4dad19e6fc810041a23342998"),
+        ];
+        for (len, expected) in vectors.iter() {
+            let mut messages = inputs[..*len].to_vec();
+            messages.push(Fr::one());
+            assert_eq!(hades_sponge_hash_with_tag(&params, Fr::zero(), &messages), from_hex(expected).unwrap());
+        }
+
+        // dusk-poseidon 0.37 `Hash::digest` of `1, 2, ..`
+        let messages: Vec<Fr> = (1..=9).map(fr).collect();
+        let vectors = [
+            (1, "2b207bd698408380fd3a2f8c88efc01eec00a1842de112cf3b9f298b6cddd350"),
+            (3, "16babbe430737810502b5fc5152ea57f55c79a8ad23ba088d5cb2741e5e8e780"),
+            (4, "6339f7d66434d2259df37b538cf5ca382452a35f2bc9392711d246b01ac889e8"),
+            (5, "32f5be897e60930fc887dbbc4883055c59bfd8d172007e256382f3d961866e15"),
+            (9, "676fb6d9389f75d1506e21c1a0d3ae5ec7669a55c4369f5451109e51da6377a4"),
+        ];
+        for (len, expected) in vectors.iter() {
+            assert_eq!(hades_sponge_hash(&params, &messages[..*len]), from_hex(expected).unwrap());
+        }
+
+        let merkle2 = from_hex("0846758a05014029160f14c6076df219fec7efe04463dea1caf0bb3e8325be7d").unwrap();
+        let merkle4 = from_hex("687177814527c49b955e3049762abd838022a71931c2bf97a7c87ac3262edced").unwrap();
+        assert_eq!(hades_merkle_hash(&params, &messages[..2]), merkle2);
+        assert_eq!(hades_merkle_hash(&params, &messages[..4]), merkle4);
+        assert!(hades_sponge_hash(&params, &messages[..4]) != merkle4);
+    }
+}
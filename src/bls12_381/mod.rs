@@ -0,0 +1,73 @@
+use pairing::bls12_381;
+use super::{PoseidonEngine, PoseidonHashParams, PoseidonParamsInternal, QuinticSBox};
+
+pub mod neptune;
+pub mod hades;
+
+impl PoseidonEngine for bls12_381::Bls12 {
+    type Params = Bls12PoseidonParams;
+}
+
+/// Params over the BLS12-381 scalar field. There is no generator of
+/// parameters of this crate's own flavour for this field, params are made
+/// by presets that follow other implementations
+#[derive(Clone)]
+pub struct Bls12PoseidonParams {
+    pub(crate) c: u32,
+    pub(crate) r: u32,
+    pub(crate) full_rounds: u32,
+    pub(crate) partial_rounds: u32,
+    pub(crate) round_constants: Vec<bls12_381::Fr>,
+    pub(crate) mds_matrix: Vec<bls12_381::Fr>,
+    pub(crate) security_level: u32,
+    pub(crate) partial_round_sbox_index: u32,
+    pub(crate) sbox: QuinticSBox<bls12_381::Bls12>,
+}
+
+impl PoseidonParamsInternal<bls12_381::Bls12> for Bls12PoseidonParams {
+    fn set_round_constants(&mut self, to: Vec<bls12_381::Fr>) {
+        assert_eq!(self.round_constants.len(), to.len());
+        self.round_constants = to;
+    }
+}
+
+impl PoseidonHashParams<bls12_381::Bls12> for Bls12PoseidonParams {
+    type SBox = QuinticSBox<bls12_381::Bls12>;
+
+    fn capacity(&self) -> u32 {
+        self.c
+    }
+    fn rate(&self) -> u32 {
+        self.r
+    }
+    fn num_full_rounds(&self) -> u32 {
+        self.full_rounds
+    }
+    fn num_partial_rounds(&self) -> u32 {
+        self.partial_rounds
+    }
+    fn round_constants(&self, round: u32) -> &[bls12_381::Fr] {
+        let t = self.c + self.r;
+        let start = (t*round) as usize;
+        let end = (t*(round+1)) as usize;
+
+        &self.round_constants[start..end]
+    }
+    fn mds_matrix_row(&self, row: u32) -> &[bls12_381::Fr] {
+        let t = self.c + self.r;
+        let start = (t*row) as usize;
+        let end = (t*(row+1)) as usize;
+
+        &self.mds_matrix[start..end]
+    }
+    fn security_level(&self) -> u32 {
+        self.security_level
+    }
+    fn partial_round_sbox_index(&self) -> u32 {
+        self.partial_round_sbox_index
+    }
+
+    fn sbox(&self) -> &Self::SBox {
+        &self.sbox
+    }
+}
@@ -0,0 +1,210 @@
+use crate::pairing::bls12_381::{Bls12, Fr};
+use crate::pairing::ff::{Field, PrimeField};
+use crate::grain::grain_round_constants_with_sbox_bits;
+use crate::{poseidon_mimc, QuinticSBox};
+use super::Bls12PoseidonParams;
+
+/// Arities supported by the neptune preset
+pub const NEPTUNE_ARITIES: [u32; 4] = [2, 4, 8, 11];
+
+const NEPTUNE_SECURITY_LEVEL: u32 = 128;
+
+/// Domain separation used by neptune, the tag is placed into the first
+/// element of the state and the preimage goes into the rest of it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NeptuneHashType {
+    /// Tag is `2^arity - 1`, preimage must fill the whole rate
+    MerkleTree,
+    /// Tag is `len * 2^64`, preimage of `len` elements is padded with zeroes
+    ConstantLength(usize),
+}
+
+impl NeptuneHashType {
+    pub fn domain_tag(&self, arity: u32) -> Fr {
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        match *self {
+            NeptuneHashType::MerkleTree => {
+                assert!(arity < 64);
+                repr.as_mut()[0] = (1u64 << arity) - 1;
+            },
+            NeptuneHashType::ConstantLength(len) => {
+                repr.as_mut()[1] = len as u64;
+            }
+        }
+
+        Fr::from_repr(repr).unwrap()
+    }
+}
+
+fn round_numbers_are_secure(t: u32, full_rounds: u32, partial_rounds: u32) -> bool {
+    // 128 bits of security over a 255 bit field, in `f32` the same way
+    // as neptune does to get exactly the same numbers
+    let (rp, t, n, m) = (partial_rounds as f32, t as f32, 255.0f32, 128.0f32);
+    let rf_stat = if m <= (n - 3.0) * (t + 1.0) { 6.0 } else { 10.0 };
+    let rf_interp = 0.43 * m + t.log2() - rp;
+    let rf_grob_1 = 0.21 * n - rp;
+    let rf_grob_2 = (0.14 * n - 1.0 - rp) / (t - 1.0);
+    let rf_max = [rf_stat, rf_interp, rf_grob_1, rf_grob_2]
+        .iter()
+        .map(|rf| rf.ceil() as i64)
+        .max()
+        .unwrap();
+
+    full_rounds as i64 >= rf_max
+}
+
+/// Number of full and partial rounds for a state of width `t` that
+/// minimizes the number of sboxes, with neptune's security margin
+/// of two full rounds and 7.5% more partial rounds
+pub fn neptune_round_numbers(t: u32) -> (u32, u32) {
+    let mut full_rounds = 0;
+    let mut partial_rounds = 0;
+    let mut min_sboxes = u32::MAX;
+
+    for rf in (2..=1000).step_by(2) {
+        for rp in 4..200 {
+            if !round_numbers_are_secure(t, rf, rp) {
+                continue;
+            }
+            let rf = rf + 2;
+            let rp = (1.075 * rp as f32).ceil() as u32;
+            let num_sboxes = t * rf + rp;
+            if num_sboxes < min_sboxes || (num_sboxes == min_sboxes && rf < full_rounds) {
+                full_rounds = rf;
+                partial_rounds = rp;
+                min_sboxes = num_sboxes;
+            }
+        }
+    }
+
+    (full_rounds, partial_rounds)
+}
+
+/// Cauchy matrix `M[i][j] = 1 / (i + (t + j))`
+pub(super) fn neptune_mds_matrix(t: u32) -> Vec<Fr> {
+    let mut matrix = Vec::with_capacity((t * t) as usize);
+    for i in 0..t {
+        for j in 0..t {
+            let mut element = Fr::from_str(&(i + t + j).to_string()).unwrap();
+            element = element.inverse().unwrap();
+            matrix.push(element);
+        }
+    }
+
+    matrix
+}
+
+impl Bls12PoseidonParams {
+    /// Params of neptune's `PoseidonConstants::<Fr, U{arity}>::new()`
+    pub fn new_neptune(arity: u32) -> Self {
+        assert!(NEPTUNE_ARITIES.contains(&arity), "arity {} is not supported by neptune preset", arity);
+        let t = arity + 1;
+        let (full_rounds, partial_rounds) = neptune_round_numbers(t);
+        // neptune describes `x^5` with 1 instead of 0 in the Grain LFSR seed
+        let (_, round_constants) = grain_round_constants_with_sbox_bits::<Fr>(1, t, full_rounds, partial_rounds);
+
+        Self {
+            c: 1,
+            r: arity,
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds_matrix: neptune_mds_matrix(t),
+            security_level: NEPTUNE_SECURITY_LEVEL,
+            partial_round_sbox_index: 0,
+            sbox: QuinticSBox { _marker: std::marker::PhantomData },
+        }
+    }
+}
+
+/// Hashes the preimage the way neptune's `Poseidon::hash` does: the tag goes
+/// into the first element, the digest is the second element of the state
+pub fn neptune_hash(params: &Bls12PoseidonParams, hash_type: NeptuneHashType, preimage: &[Fr]) -> Fr {
+    let arity = params.r;
+    match hash_type {
+        NeptuneHashType::MerkleTree => {
+            assert_eq!(preimage.len(), arity as usize, "merkle tree hash takes exactly arity elements");
+        },
+        NeptuneHashType::ConstantLength(len) => {
+            assert_eq!(preimage.len(), len, "preimage length must match the hash type");
+            assert!(len <= arity as usize, "preimage is longer than arity");
+        }
+    }
+
+    let mut state = vec![Fr::zero(); (arity + 1) as usize];
+    state[0] = hash_type.domain_tag(arity);
+    state[1..(1 + preimage.len())].copy_from_slice(preimage);
+
+    let state = poseidon_mimc::<Bls12>(params, &state);
+
+    state[1]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::PoseidonHashParams;
+    use crate::inverse::invert_matrix;
+    use super::*;
+
+    #[test]
+    fn test_neptune_presets() {
+        let expected_rounds = [(8, 55), (8, 56), (8, 57), (8, 57)];
+        for (arity, expected) in NEPTUNE_ARITIES.iter().zip(expected_rounds.iter()) {
+            let params = Bls12PoseidonParams::new_neptune(*arity);
+            assert_eq!((params.num_full_rounds(), params.num_partial_rounds()), *expected);
+            assert_eq!(params.state_width(), arity + 1);
+            assert_eq!(params.round_constants.len() as u32, (arity + 1) * (expected.0 + expected.1));
+            assert_eq!(params.partial_round_sbox_index(), 0);
+
+            let t = arity + 1;
+            for i in 0..t {
+                for j in 0..t {
+                    assert_eq!(params.mds_matrix_row(i)[j as usize], params.mds_matrix_row(j)[i as usize]);
+                }
+            }
+            assert!(invert_matrix::<Bls12>(&params.mds_matrix, t as usize).is_some());
+        }
+    }
+
+    #[test]
+    fn test_neptune_hash_vectors() {
+        // `hash_values` of neptune 13.0.0 for the standard strength, merkle
+        // tree hash of `0, 1, ..., arity - 1`, limbs are little endian
+        use crate::pairing::bls12_381::FrRepr;
+
+        let expected = [
+            (2, [0x2e203c369a02e7ff, 0xa6fba9339d05a69d, 0x739e0fd902efe161, 0x396508d75e76a56b]),
+            (4, [0x019814ff6662075d, 0xfb6b4605bf1327ec, 0x00db3c6579229399, 0x58a54b10a9e5848a]),
+            (8, [0x2a9934f56d38a5e6, 0x4b682e9d9cc4aed9, 0x1201004211677077, 0x2394611da3a5de55]),
+            (11, [0xcee3bbc32b693163, 0x09f3dcd8ccb08fc1, 0x6ca537e232ebe87a, 0x0c0fc1b2e5227f28]),
+        ];
+        for (arity, digest) in expected.iter() {
+            let params = Bls12PoseidonParams::new_neptune(*arity);
+            let preimage: Vec<Fr> = (0..*arity).map(|i| Fr::from_str(&i.to_string()).unwrap()).collect();
+            let digest = Fr::from_repr(FrRepr(*digest)).unwrap();
+            assert_eq!(neptune_hash(&params, NeptuneHashType::MerkleTree, &preimage), digest, "arity {}", arity);
+        }
+    }
+
+    #[test]
+    fn test_neptune_hash_domain_separation() {
+        assert_eq!(NeptuneHashType::MerkleTree.domain_tag(2), Fr::from_str("3").unwrap());
+        assert_eq!(NeptuneHashType::MerkleTree.domain_tag(11), Fr::from_str("2047").unwrap());
+        assert_eq!(NeptuneHashType::ConstantLength(3).domain_tag(4), Fr::from_str("55340232221128654848").unwrap());
+
+        let params = Bls12PoseidonParams::new_neptune(4);
+        let preimage: Vec<Fr> = (0..4).map(|i| Fr::from_str(&i.to_string()).unwrap()).collect();
+        let merkle = neptune_hash(&params, NeptuneHashType::MerkleTree, &preimage);
+
+        let mut state = vec![NeptuneHashType::MerkleTree.domain_tag(4)];
+        state.extend_from_slice(&preimage);
+        assert_eq!(merkle, poseidon_mimc::<Bls12>(&params, &state)[1]);
+
+        // zero padding doesn't collide with the merkle tree mode
+        let short = neptune_hash(&params, NeptuneHashType::ConstantLength(3), &preimage[..3]);
+        let mut padded = preimage.clone();
+        padded[3] = Fr::zero();
+        assert!(short != neptune_hash(&params, NeptuneHashType::MerkleTree, &padded));
+        assert!(short != merkle);
+    }
+}
@@ -0,0 +1,152 @@
+use pairing::bn256;
+use crate::group_hash::{GroupHasher, PERSONALIZATION_LEN};
+use crate::sampling::ParamsSeeds;
+use crate::{ParamsError, check_security_level, check_shape};
+use super::Bn256PoseidonParams;
+
+/// Validating builder for `Bn256PoseidonParams`. Starts from the shape of
+/// `new_checked_2_into_1` and the default seeds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonParamsBuilder {
+    capacity: u32,
+    rate: u32,
+    full_rounds: u32,
+    partial_rounds: u32,
+    security_level: u32,
+    seeds: ParamsSeeds,
+}
+
+impl Default for PoseidonParamsBuilder {
+    fn default() -> Self {
+        Self {
+            capacity: 1,
+            rate: 2,
+            full_rounds: 8,
+            partial_rounds: 83,
+            security_level: 126,
+            seeds: ParamsSeeds::default(),
+        }
+    }
+}
+
+impl PoseidonParamsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn capacity(mut self, capacity: u32) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn rate(mut self, rate: u32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn full_rounds(mut self, full_rounds: u32) -> Self {
+        self.full_rounds = full_rounds;
+        self
+    }
+
+    pub fn partial_rounds(mut self, partial_rounds: u32) -> Self {
+        self.partial_rounds = partial_rounds;
+        self
+    }
+
+    pub fn security_level(mut self, security_level: u32) -> Self {
+        self.security_level = security_level;
+        self
+    }
+
+    pub fn seeds(mut self, seeds: ParamsSeeds) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        // output length of the bn256 params is the capacity
+        check_shape(self.rate, self.capacity, self.full_rounds, self.partial_rounds, self.capacity)?;
+
+        for tag in [&self.seeds.round_constants_tag, &self.seeds.mds_tag].iter() {
+            if tag.len() > PERSONALIZATION_LEN {
+                return Err(ParamsError::PersonalizationTooLong { len: tag.len() });
+            }
+        }
+
+        check_security_level::<bn256::Fr>(self.capacity, self.security_level)
+    }
+
+    pub fn build<H: GroupHasher>(&self) -> Result<Bn256PoseidonParams, ParamsError> {
+        self.validate()?;
+
+        Ok(Bn256PoseidonParams::new_with_seeds::<H>(
+            self.capacity,
+            self.rate,
+            self.partial_rounds,
+            self.full_rounds,
+            self.security_level,
+            self.seeds.clone()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::ff::Field;
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::group_hash::BlakeHasher;
+    use crate::specialization::PoseidonSponge;
+    use crate::{StatefulSponge, try_poseidon_hash, try_poseidon_mimc, poseidon_hash};
+    use super::*;
+
+    #[test]
+    fn test_builder_errors() {
+        let builder = PoseidonParamsBuilder::new();
+        assert_eq!(builder.clone().rate(0).validate(), Err(ParamsError::ZeroRate));
+        assert_eq!(builder.clone().capacity(0).validate(), Err(ParamsError::ZeroCapacity));
+        assert_eq!(builder.clone().full_rounds(7).validate(), Err(ParamsError::OddNumberOfFullRounds(7)));
+        assert_eq!(builder.clone().full_rounds(0).partial_rounds(0).validate(), Err(ParamsError::NoRounds));
+        assert_eq!(
+            builder.clone().rate(1).capacity(2).validate(),
+            Err(ParamsError::RateBelowOutputLength { rate: 1, output_len: 2 })
+        );
+        let long_tag = ParamsSeeds::default().with_mds_tag(b"TooLongTag");
+        assert_eq!(builder.clone().seeds(long_tag).validate(), Err(ParamsError::PersonalizationTooLong { len: 10 }));
+        assert_eq!(
+            builder.clone().security_level(0).validate(),
+            Err(ParamsError::InvalidSecurityLevel { security_level: 0, max: 126 })
+        );
+        assert_eq!(
+            builder.clone().security_level(127).validate(),
+            Err(ParamsError::InvalidSecurityLevel { security_level: 127, max: 126 })
+        );
+        assert!(builder.clone().capacity(2).rate(2).security_level(253).validate().is_ok());
+
+        let params = builder.build::<BlakeHasher>().unwrap();
+        let checked = Bn256PoseidonParams::new_checked_2_into_1();
+        let input = [Fr::zero(), Fr::one()];
+        assert_eq!(try_poseidon_hash::<Bn256>(&params, &input).unwrap(), poseidon_hash::<Bn256>(&checked, &input));
+    }
+
+    #[test]
+    fn test_fallible_functions() {
+        let params = PoseidonParamsBuilder::new().build::<BlakeHasher>().unwrap();
+        assert_eq!(
+            try_poseidon_mimc::<Bn256>(&params, &[Fr::one(); 2]),
+            Err(ParamsError::InvalidStateLength { expected: 3, actual: 2 })
+        );
+        assert!(try_poseidon_mimc::<Bn256>(&params, &[Fr::one(); 3]).is_ok());
+        assert!(StatefulSponge::<Bn256>::try_new(&params).is_ok());
+        assert!(PoseidonSponge::<Bn256, 2, 1>::try_new(&params).is_ok());
+        assert_eq!(
+            PoseidonSponge::<Bn256, 3, 1>::try_new(&params).err(),
+            Some(ParamsError::ShapeMismatch { expected_rate: 3, expected_capacity: 1, rate: 2, capacity: 1 })
+        );
+
+        // params that bypass the builder are checked too
+        let odd = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 10, 3, 80);
+        assert_eq!(try_poseidon_hash::<Bn256>(&odd, &[Fr::one()]), Err(ParamsError::OddNumberOfFullRounds(3)));
+        assert_eq!(StatefulSponge::<Bn256>::try_new(&odd).err(), Some(ParamsError::OddNumberOfFullRounds(3)));
+    }
+}
@@ -0,0 +1,605 @@
+//! Code generation for recomputing `poseidon_hash` on chain, modeled after
+//! circomlib's `poseidon_gencontract`. The contract has a single function
+//! `poseidon(uint256[n])` (also callable as `poseidon(bytes32[n])` in the
+//! bytecode) for a number of inputs fixed at generation time, because
+//! padding depends on it. Inputs are taken modulo the characteristic and
+//! `capacity` words of the digest are returned.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use pairing::bn256;
+use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+use crate::{check_params, ParamsError, PoseidonHashParams, RoundConstantsPosition};
+use super::Bn256PoseidonParams;
+
+/// Largest runtime code that can be deployed on Ethereum (EIP-170)
+pub const EVM_CODE_SIZE_LIMIT: usize = 24576;
+
+const ADDMOD: u8 = 0x08;
+const MULMOD: u8 = 0x09;
+const EQ: u8 = 0x14;
+const OR: u8 = 0x17;
+const SHR: u8 = 0x1c;
+const CALLDATALOAD: u8 = 0x35;
+const CODECOPY: u8 = 0x39;
+const MLOAD: u8 = 0x51;
+const MSTORE: u8 = 0x52;
+const JUMP: u8 = 0x56;
+const JUMPI: u8 = 0x57;
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const DUP1: u8 = 0x80;
+const SWAP1: u8 = 0x90;
+const RETURN: u8 = 0xf3;
+const INVALID: u8 = 0xfe;
+
+/// Straight line code with jumps to named labels, jump targets are
+/// always pushed as two bytes and patched in `finish`
+struct Assembler {
+    code: Vec<u8>,
+    labels: HashMap<String, usize>,
+    references: Vec<(usize, String)>,
+}
+
+impl Assembler {
+    fn new() -> Self {
+        Self { code: vec![], labels: HashMap::new(), references: vec![] }
+    }
+
+    fn op(&mut self, opcode: u8) {
+        self.code.push(opcode);
+    }
+
+    fn dup(&mut self, n: u8) {
+        assert!((1..=16).contains(&n));
+        self.op(DUP1 + n - 1);
+    }
+
+    fn swap(&mut self, n: u8) {
+        assert!((1..=16).contains(&n));
+        self.op(SWAP1 + n - 1);
+    }
+
+    /// Pushes big endian bytes with the shortest `PUSH`
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        let first_nonzero = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len() - 1);
+        let bytes = &bytes[first_nonzero..];
+        assert!(!bytes.is_empty() && bytes.len() <= 32);
+        self.op(PUSH1 + (bytes.len() as u8) - 1);
+        self.code.extend_from_slice(bytes);
+    }
+
+    fn push(&mut self, value: usize) {
+        self.push_bytes(&(value as u64).to_be_bytes());
+    }
+
+    fn push_field(&mut self, value: &bn256::Fr) {
+        let mut bytes = Vec::with_capacity(32);
+        value.into_repr().write_be(&mut bytes).unwrap();
+        self.push_bytes(&bytes);
+    }
+
+    fn push_label(&mut self, label: &str) {
+        self.op(PUSH1 + 1);
+        self.references.push((self.code.len(), label.to_owned()));
+        self.code.extend_from_slice(&[0, 0]);
+    }
+
+    fn label(&mut self, label: &str) {
+        assert!(self.labels.insert(label.to_owned(), self.code.len()).is_none(), "label {} is defined twice", label);
+        self.op(JUMPDEST);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        for (position, label) in self.references.iter() {
+            let target = *self.labels.get(label).unwrap_or_else(|| panic!("label {} is not defined", label));
+            assert!(target <= u16::MAX as usize);
+            self.code[*position..(*position + 2)].copy_from_slice(&(target as u16).to_be_bytes());
+        }
+
+        self.code
+    }
+}
+
+/// Memory layout of the generated code: the characteristic, the state and
+/// scratch space for the MDS multiplication
+struct Layout {
+    t: usize,
+}
+
+impl Layout {
+    const MODULUS: usize = 0;
+
+    fn state(&self, i: usize) -> usize {
+        32 * (1 + i)
+    }
+
+    fn scratch(&self, i: usize) -> usize {
+        32 * (1 + self.t + i)
+    }
+}
+
+fn load(asm: &mut Assembler, offset: usize) {
+    asm.push(offset);
+    asm.op(MLOAD);
+}
+
+fn store(asm: &mut Assembler, offset: usize) {
+    asm.push(offset);
+    asm.op(MSTORE);
+}
+
+fn add_round_constants(asm: &mut Assembler, layout: &Layout, constants: &[bn256::Fr]) {
+    for (i, constant) in constants.iter().enumerate() {
+        load(asm, Layout::MODULUS);
+        asm.push_field(constant);
+        load(asm, layout.state(i));
+        asm.op(ADDMOD);
+        store(asm, layout.state(i));
+    }
+}
+
+fn quintic_sbox(asm: &mut Assembler, layout: &Layout, i: usize) {
+    load(asm, Layout::MODULUS);     // q
+    load(asm, layout.state(i));     // q, x
+    asm.dup(2);
+    asm.dup(2);
+    asm.dup(1);
+    asm.op(MULMOD);                 // q, x, x^2
+    asm.dup(3);
+    asm.swap(1);
+    asm.dup(1);
+    asm.op(MULMOD);                 // q, x, x^4
+    asm.op(MULMOD);                 // x^5
+    store(asm, layout.state(i));
+}
+
+/// Subroutine that multiplies the state by the MDS matrix, the return
+/// address is on top of the stack
+fn mix_subroutine(asm: &mut Assembler, layout: &Layout, params: &Bn256PoseidonParams) {
+    asm.label("mix");
+    for i in 0..layout.t {
+        for (j, element) in params.mds_matrix_row(i as u32).iter().enumerate() {
+            if j != 0 {
+                load(asm, Layout::MODULUS);
+                asm.swap(1);            // q, acc
+            }
+            load(asm, Layout::MODULUS);
+            load(asm, layout.state(j));
+            asm.push_field(element);
+            asm.op(MULMOD);             // [q, acc,] M_ij * s_j
+            if j != 0 {
+                asm.op(ADDMOD);
+            }
+        }
+        store(asm, layout.scratch(i));
+    }
+    for i in 0..layout.t {
+        load(asm, layout.scratch(i));
+        store(asm, layout.state(i));
+    }
+    asm.op(JUMP);
+}
+
+/// Subroutine with the whole permutation, the return address is on top of the stack
+fn permutation_subroutine(asm: &mut Assembler, layout: &Layout, params: &Bn256PoseidonParams) {
+    let constants_first = params.round_constants_position() == RoundConstantsPosition::BeforeSBox;
+    let half_of_full_rounds = params.num_full_rounds() / 2;
+    let partial_rounds_end = half_of_full_rounds + params.num_partial_rounds();
+    let sbox_idx = params.partial_round_sbox_index() as usize;
+
+    asm.label("permute");
+    for round in 0..(params.num_full_rounds() + params.num_partial_rounds()) {
+        if constants_first {
+            add_round_constants(asm, layout, params.round_constants(round));
+        }
+
+        if round < half_of_full_rounds || round >= partial_rounds_end {
+            for i in 0..layout.t {
+                quintic_sbox(asm, layout, i);
+            }
+        } else {
+            quintic_sbox(asm, layout, sbox_idx);
+        }
+
+        let after_mix = format!("after_mix_{}", round);
+        asm.push_label(&after_mix);
+        asm.push_label("mix");
+        asm.op(JUMP);
+        asm.label(&after_mix);
+
+        if !constants_first {
+            add_round_constants(asm, layout, params.round_constants(round));
+        }
+    }
+    asm.op(JUMP);
+}
+
+/// Absorbed inputs of each permutation, `None` is a padding element
+fn absorption_blocks(rate: usize, num_inputs: usize) -> Vec<Vec<Option<usize>>> {
+    let num_blocks = std::cmp::max(num_inputs.div_ceil(rate), 1);
+
+    (0..num_blocks)
+        .map(|block| (0..rate).map(|j| Some(block * rate + j).filter(|k| *k < num_inputs)).collect())
+        .collect()
+}
+
+/// Signature of the generated function
+pub fn function_signature(num_inputs: usize) -> String {
+    format!("poseidon(uint256[{}])", num_inputs)
+}
+
+/// First four bytes of the keccak256 of the signature
+pub fn function_selector(signature: &str) -> [u8; 4] {
+    let hash = tiny_keccak::keccak256(signature.as_bytes());
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&hash[..4]);
+
+    selector
+}
+
+/// Runtime bytecode that hashes `num_inputs` elements the same way as
+/// `poseidon_hash` does. Code larger than `EVM_CODE_SIZE_LIMIT` can not be
+/// deployed on Ethereum and is an error, the limit holds for `t <= 4` and
+/// the default round numbers
+pub fn evm_runtime_bytecode(params: &Bn256PoseidonParams, num_inputs: usize) -> Result<Vec<u8>, ParamsError> {
+    check_params(params)?;
+    if num_inputs == 0 {
+        return Err(ParamsError::NoInputs);
+    }
+    let layout = Layout { t: params.state_width() as usize };
+    let mut asm = Assembler::new();
+
+    // dispatch on the selector
+    asm.push(0);
+    asm.op(CALLDATALOAD);
+    asm.push(0xe0);
+    asm.op(SHR);
+    asm.dup(1);
+    asm.push_bytes(&function_selector(&function_signature(num_inputs)));
+    asm.op(EQ);
+    asm.swap(1);
+    asm.push_bytes(&function_selector(&format!("poseidon(bytes32[{}])", num_inputs)));
+    asm.op(EQ);
+    asm.op(OR);
+    asm.push_label("start");
+    asm.op(JUMPI);
+    asm.op(INVALID);
+
+    asm.label("start");
+    let mut modulus = Vec::with_capacity(32);
+    bn256::Fr::char().write_be(&mut modulus).unwrap();
+    asm.push_bytes(&modulus);
+    store(&mut asm, Layout::MODULUS);
+
+    let blocks = absorption_blocks(params.rate() as usize, num_inputs);
+    for (block_idx, block) in blocks.iter().enumerate() {
+        for (j, input) in block.iter().enumerate() {
+            load(&mut asm, Layout::MODULUS);
+            match input {
+                Some(k) => {
+                    asm.push(4 + 32 * k);
+                    asm.op(CALLDATALOAD);
+                },
+                None => asm.push(1),
+            }
+            load(&mut asm, layout.state(j));
+            asm.op(ADDMOD);
+            store(&mut asm, layout.state(j));
+        }
+
+        let after_permute = format!("after_permute_{}", block_idx);
+        asm.push_label(&after_permute);
+        asm.push_label("permute");
+        asm.op(JUMP);
+        asm.label(&after_permute);
+    }
+
+    // the digest is the first `capacity` elements of the state
+    asm.push(32 * params.capacity() as usize);
+    asm.push(layout.state(0));
+    asm.op(RETURN);
+
+    permutation_subroutine(&mut asm, &layout, params);
+    mix_subroutine(&mut asm, &layout, params);
+
+    let code = asm.finish();
+    if code.len() > EVM_CODE_SIZE_LIMIT {
+        return Err(ParamsError::CodeSizeLimitExceeded { size: code.len(), limit: EVM_CODE_SIZE_LIMIT });
+    }
+
+    Ok(code)
+}
+
+/// Creation code that deploys `evm_runtime_bytecode`
+pub fn evm_deployment_bytecode(params: &Bn256PoseidonParams, num_inputs: usize) -> Result<Vec<u8>, ParamsError> {
+    // the size limit also keeps the length within the two byte push below
+    let runtime = evm_runtime_bytecode(params, num_inputs)?;
+    const INIT_CODE_LEN: usize = 13;
+
+    // copy the code that follows to memory and return it
+    let mut code = Vec::with_capacity(INIT_CODE_LEN + runtime.len());
+    code.push(PUSH1 + 1);
+    code.extend_from_slice(&(runtime.len() as u16).to_be_bytes());
+    code.push(DUP1);
+    code.extend_from_slice(&[PUSH1 + 1, 0, INIT_CODE_LEN as u8]);
+    code.extend_from_slice(&[PUSH1, 0]);
+    code.push(CODECOPY);
+    code.extend_from_slice(&[PUSH1, 0]);
+    code.push(RETURN);
+    assert_eq!(code.len(), INIT_CODE_LEN);
+    code.extend_from_slice(&runtime);
+
+    Ok(code)
+}
+
+fn field_literal(value: &bn256::Fr) -> String {
+    format!("{}", value.into_repr())
+}
+
+/// Solidity contract with the same function as `evm_runtime_bytecode`.
+/// Solidity has no fixed size arrays of length zero, so `num_inputs` must
+/// be positive
+pub fn solidity_source(params: &Bn256PoseidonParams, num_inputs: usize, contract_name: &str) -> Result<String, ParamsError> {
+    check_params(params)?;
+    if num_inputs == 0 {
+        return Err(ParamsError::NoInputs);
+    }
+    let t = params.state_width() as usize;
+    let c = params.capacity() as usize;
+    let constants_first = params.round_constants_position() == RoundConstantsPosition::BeforeSBox;
+    let half_of_full_rounds = params.num_full_rounds() / 2;
+    let partial_rounds_end = half_of_full_rounds + params.num_partial_rounds();
+    let sbox_idx = params.partial_round_sbox_index() as usize;
+
+    let mut s = String::new();
+    writeln!(s, "// SPDX-License-Identifier: MIT").unwrap();
+    writeln!(s, "pragma solidity >=0.7.0;").unwrap();
+    writeln!(s).unwrap();
+    writeln!(s, "/// Poseidon over the bn254 scalar field with t = {}, {} full and {} partial rounds,", t, params.num_full_rounds(), params.num_partial_rounds()).unwrap();
+    writeln!(s, "/// generated by poseidon_hash").unwrap();
+    writeln!(s, "contract {} {{", contract_name).unwrap();
+    writeln!(s, "    uint256 constant Q = {};", bn256::Fr::char()).unwrap();
+    writeln!(s).unwrap();
+
+    let output_type = if c == 1 { "uint256".to_owned() } else { format!("uint256[{}] memory", c) };
+    writeln!(s, "    function poseidon(uint256[{}] memory inputs) public pure returns ({} output) {{", num_inputs, output_type).unwrap();
+    writeln!(s, "        uint256[{}] memory state;", t).unwrap();
+    for block in absorption_blocks(params.rate() as usize, num_inputs) {
+        for (j, input) in block.iter().enumerate() {
+            match input {
+                Some(k) => writeln!(s, "        state[{}] = addmod(state[{}], inputs[{}], Q);", j, j, k).unwrap(),
+                None => writeln!(s, "        state[{}] = addmod(state[{}], 1, Q);", j, j).unwrap(),
+            }
+        }
+        writeln!(s, "        permute(state);").unwrap();
+    }
+    if c == 1 {
+        writeln!(s, "        output = state[0];").unwrap();
+    } else {
+        for i in 0..c {
+            writeln!(s, "        output[{}] = state[{}];", i, i).unwrap();
+        }
+    }
+    writeln!(s, "    }}").unwrap();
+    writeln!(s).unwrap();
+
+    writeln!(s, "    function sbox(uint256 x) private pure returns (uint256) {{").unwrap();
+    writeln!(s, "        uint256 x2 = mulmod(x, x, Q);").unwrap();
+    writeln!(s, "        return mulmod(mulmod(x2, x2, Q), x, Q);").unwrap();
+    writeln!(s, "    }}").unwrap();
+    writeln!(s).unwrap();
+
+    writeln!(s, "    function mix(uint256[{}] memory s) private pure {{", t).unwrap();
+    writeln!(s, "        uint256[{}] memory n;", t).unwrap();
+    for i in 0..t {
+        for (j, element) in params.mds_matrix_row(i as u32).iter().enumerate() {
+            if j == 0 {
+                writeln!(s, "        n[{}] = mulmod({}, s[{}], Q);", i, field_literal(element), j).unwrap();
+            } else {
+                writeln!(s, "        n[{}] = addmod(n[{}], mulmod({}, s[{}], Q), Q);", i, i, field_literal(element), j).unwrap();
+            }
+        }
+    }
+    for i in 0..t {
+        writeln!(s, "        s[{}] = n[{}];", i, i).unwrap();
+    }
+    writeln!(s, "    }}").unwrap();
+    writeln!(s).unwrap();
+
+    writeln!(s, "    function permute(uint256[{}] memory s) private pure {{", t).unwrap();
+    for round in 0..(params.num_full_rounds() + params.num_partial_rounds()) {
+        let write_constants = |s: &mut String| {
+            for (i, constant) in params.round_constants(round).iter().enumerate() {
+                writeln!(s, "        s[{}] = addmod(s[{}], {}, Q);", i, i, field_literal(constant)).unwrap();
+            }
+        };
+        writeln!(s, "        // round {}", round).unwrap();
+        if constants_first {
+            write_constants(&mut s);
+        }
+        if round < half_of_full_rounds || round >= partial_rounds_end {
+            for i in 0..t {
+                writeln!(s, "        s[{}] = sbox(s[{}]);", i, i).unwrap();
+            }
+        } else {
+            writeln!(s, "        s[{}] = sbox(s[{}]);", sbox_idx, sbox_idx).unwrap();
+        }
+        writeln!(s, "        mix(s);").unwrap();
+        if !constants_first {
+            write_constants(&mut s);
+        }
+    }
+    writeln!(s, "    }}").unwrap();
+    writeln!(s, "}}").unwrap();
+
+    Ok(s)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use revm::{Evm, InMemoryDB};
+    use revm::primitives::{hex, AccountInfo, Address, Bytecode, Bytes, ExecutionResult, Output, TxKind, U256};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::group_hash::BlakeHasher;
+    use crate::poseidon_hash;
+    use super::*;
+
+    fn run(evm: &mut Evm<'_, (), InMemoryDB>, to: TxKind, data: Vec<u8>) -> Output {
+        evm.tx_mut().transact_to = to;
+        evm.tx_mut().data = Bytes::from(data);
+        evm.tx_mut().gas_limit = 30_000_000;
+        match evm.transact_commit().unwrap() {
+            ExecutionResult::Success { output, .. } => output,
+            result => panic!("execution failed: {:?}", result),
+        }
+    }
+
+    fn check_against_native(params: &Bn256PoseidonParams, num_inputs: usize) {
+        let rng = &mut thread_rng();
+        let mut evm = Evm::builder().with_db(InMemoryDB::default()).build();
+        let address = match run(&mut evm, TxKind::Create, evm_deployment_bytecode(params, num_inputs).unwrap()) {
+            Output::Create(code, Some(address)) => {
+                assert_eq!(code.to_vec(), evm_runtime_bytecode(params, num_inputs).unwrap());
+                address
+            },
+            output => panic!("unexpected output {:?}", output),
+        };
+
+        let input: Vec<Fr> = (0..num_inputs).map(|_| rng.gen()).collect();
+        let mut calldata = function_selector(&function_signature(num_inputs)).to_vec();
+        for element in input.iter() {
+            element.into_repr().write_be(&mut calldata).unwrap();
+        }
+        let output = match run(&mut evm, TxKind::Call(address), calldata) {
+            Output::Call(output) => output,
+            output => panic!("unexpected output {:?}", output),
+        };
+
+        let mut expected = vec![];
+        for element in poseidon_hash::<Bn256>(params, &input).iter() {
+            element.into_repr().write_be(&mut expected).unwrap();
+        }
+        assert_eq!(output.to_vec(), expected, "t = {}, {} inputs", params.state_width(), num_inputs);
+    }
+
+    // calls runtime code installed directly, so it may exceed the size limit
+    fn call_code(code: Vec<u8>, calldata: Vec<u8>) -> Vec<u8> {
+        let address = Address::repeat_byte(0x42);
+        let code = Bytecode::new_raw(Bytes::from(code));
+        let mut db = InMemoryDB::default();
+        db.insert_account_info(address, AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code));
+
+        let mut evm = Evm::builder().with_db(db).build();
+        match run(&mut evm, TxKind::Call(address), calldata) {
+            Output::Call(output) => output.to_vec(),
+            output => panic!("unexpected output {:?}", output),
+        }
+    }
+
+    // runtime code of `contract_name` compiled by `solc` from the `SOLC`
+    // environment variable or the PATH
+    fn compile_solidity(source: &str, contract_name: &str) -> Vec<u8> {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        let solc = std::env::var("SOLC").unwrap_or_else(|_| "solc".to_owned());
+        let mut child = Command::new(solc)
+            .args(["--optimize", "--bin-runtime", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("solc must be on the PATH or set in SOLC");
+        child.stdin.take().unwrap().write_all(source.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "solc failed to compile the generated source");
+
+        // the code follows `Binary of the runtime part:` in the section of the contract
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let section = stdout.split(&format!(":{} =======", contract_name)).nth(1).expect("contract is missing in the solc output");
+        let code = section.lines().skip_while(|line| !line.starts_with("Binary of the runtime part")).nth(1).unwrap();
+
+        hex::decode(code.trim()).unwrap()
+    }
+
+    #[test]
+    fn test_evm_bytecode_matches_native() {
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        for num_inputs in [1, 2, 3, 5].iter() {
+            check_against_native(&params, *num_inputs);
+        }
+        assert_eq!(evm_runtime_bytecode(&params, 0), Err(ParamsError::NoInputs));
+        assert_eq!(evm_deployment_bytecode(&params, 0), Err(ParamsError::NoInputs));
+
+        let params = Bn256PoseidonParams::new_3_into_1::<BlakeHasher>();
+        check_against_native(&params, 3);
+        let params = Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 2, 40, 8, 80);
+        check_against_native(&params, 4);
+
+        let wide = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 8, 60, 8, 80);
+        match evm_deployment_bytecode(&wide, 8) {
+            Err(ParamsError::CodeSizeLimitExceeded { size, limit }) => assert!(size > limit && limit == EVM_CODE_SIZE_LIMIT),
+            result => panic!("unexpected result {:?}", result.map(|code| code.len())),
+        }
+    }
+
+    #[test]
+    fn test_evm_rejects_unknown_selector() {
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let mut evm = Evm::builder().with_db(InMemoryDB::default()).build();
+        let address = match run(&mut evm, TxKind::Create, evm_deployment_bytecode(&params, 2).unwrap()) {
+            Output::Create(_, Some(address)) => address,
+            output => panic!("unexpected output {:?}", output),
+        };
+        assert_eq!(function_selector("poseidon(uint256[2])"), [0x29, 0xa5, 0xf2, 0xf6]);
+
+        evm.tx_mut().transact_to = TxKind::Call(address);
+        evm.tx_mut().data = Bytes::from(function_selector(&function_signature(3)).to_vec());
+        evm.tx_mut().gas_limit = 1_000_000;
+        assert!(!evm.transact_commit().unwrap().is_success());
+    }
+
+    #[test]
+    fn test_solidity_source() {
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let source = solidity_source(&params, 2, "PoseidonT3").unwrap();
+        assert!(source.contains("contract PoseidonT3 {"));
+        assert!(source.contains("function poseidon(uint256[2] memory inputs) public pure returns (uint256 output)"));
+        assert!(source.contains(&format!("s[0] = addmod(s[0], {}, Q);", params.round_constants(0)[0].into_repr())));
+        assert!(source.contains(&format!("n[2] = addmod(n[2], mulmod({}, s[2], Q), Q);", params.mds_matrix_row(2)[2].into_repr())));
+        assert_eq!(source.matches("mix(s);").count(), 91);
+        // partial rounds apply the sbox to the last element only
+        assert_eq!(source.matches("s[2] = sbox(s[2]);").count(), 91);
+        assert_eq!(source.matches("s[0] = sbox(s[0]);").count(), 8);
+
+        assert_eq!(solidity_source(&params, 0, "PoseidonT3"), Err(ParamsError::NoInputs));
+    }
+
+    // needs a Solidity compiler, run with `SOLC=<path> cargo test -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_solidity_matches_bytecode() {
+        let rng = &mut thread_rng();
+        let cases = [
+            (Bn256PoseidonParams::new_checked_2_into_1(), 2),
+            (Bn256PoseidonParams::new_checked_2_into_1(), 5),
+            (Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 2, 40, 8, 80), 3),
+        ];
+        for (params, num_inputs) in cases.iter() {
+            let source = solidity_source(params, *num_inputs, "Poseidon").unwrap();
+            let compiled = compile_solidity(&source, "Poseidon");
+
+            let input: Vec<Fr> = (0..*num_inputs).map(|_| rng.gen()).collect();
+            let mut calldata = function_selector(&function_signature(*num_inputs)).to_vec();
+            for element in input.iter() {
+                element.into_repr().write_be(&mut calldata).unwrap();
+            }
+            assert_eq!(
+                call_code(compiled, calldata.clone()),
+                call_code(evm_runtime_bytecode(params, *num_inputs).unwrap(), calldata),
+                "t = {}, {} inputs", params.state_width(), num_inputs
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Parameters generated the way franklin-crypto does for the legacy zkSync code.
+//! franklin-crypto has no Poseidon parameters of its own, its Poseidon gadget
+//! takes them from this crate, but its `Bn256RescueParams` use the same
+//! generation: round constants are sampled by rejection from
+//! `H(b"Rescue_f"; GH_FIRST_BLOCK || nonce)` and the MDS matrix is a Cauchy
+//! matrix from a ChaCha RNG seeded by `H(b"ResM0003"; GH_FIRST_BLOCK)`. The only
+//! difference is the RNG seed, that franklin-crypto fills with the first big
+//! endian word of the digest eight times, so the MDS matrix differs while round
+//! constants, rounds and the sbox are the same. Use `params_differences` to
+//! check migrated parameters.
+
+use pairing::bn256;
+use crate::PoseidonHashParams;
+use crate::group_hash::{GroupHasher, BlakeHasher};
+use crate::sampling::{MdsSeedDerivation, ParamsSeeds};
+use super::Bn256PoseidonParams;
+
+/// Seeds that reproduce franklin-crypto parameters
+pub fn franklin_seeds() -> ParamsSeeds {
+    ParamsSeeds::default().with_mds_seed_derivation(MdsSeedDerivation::RepeatedFirstWord)
+}
+
+impl Bn256PoseidonParams {
+    pub fn new_franklin_checked_2_into_1() -> Self {
+        Self::new_franklin_2_into_1::<BlakeHasher>()
+    }
+
+    pub fn new_franklin_2_into_1<H: GroupHasher>() -> Self {
+        Self::new_franklin_for_params::<H>(1, 2, 83, 8, 126)
+    }
+
+    pub fn new_franklin_3_into_1<H: GroupHasher>() -> Self {
+        Self::new_franklin_for_params::<H>(1, 3, 83, 8, 126)
+    }
+
+    pub fn new_franklin_for_params<H: GroupHasher>(c: u32, r: u32, partial_rounds: u32, full_rounds: u32, security_level: u32) -> Self {
+        Self::new_with_seeds::<H>(c, r, partial_rounds, full_rounds, security_level, franklin_seeds())
+    }
+}
+
+/// A way two parameter sets disagree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamsDifference {
+    Capacity { ours: u32, theirs: u32 },
+    Rate { ours: u32, theirs: u32 },
+    FullRounds { ours: u32, theirs: u32 },
+    PartialRounds { ours: u32, theirs: u32 },
+    SecurityLevel { ours: u32, theirs: u32 },
+    /// Index of the first different constant, counted over all rounds
+    RoundConstants { first_mismatch: usize },
+    /// Index of the first different element of the row major matrix
+    MdsMatrix { first_mismatch: usize },
+}
+
+/// Lists every difference between two parameter sets, an empty list means
+/// they give the same permutation. Constants are only compared if the
+/// shapes are the same
+pub fn params_differences(ours: &Bn256PoseidonParams, theirs: &Bn256PoseidonParams) -> Vec<ParamsDifference> {
+    let mut differences = vec![];
+    let shape = [
+        (ours.c, theirs.c, ParamsDifference::Capacity { ours: ours.c, theirs: theirs.c }),
+        (ours.r, theirs.r, ParamsDifference::Rate { ours: ours.r, theirs: theirs.r }),
+        (ours.full_rounds, theirs.full_rounds, ParamsDifference::FullRounds { ours: ours.full_rounds, theirs: theirs.full_rounds }),
+        (ours.partial_rounds, theirs.partial_rounds, ParamsDifference::PartialRounds { ours: ours.partial_rounds, theirs: theirs.partial_rounds }),
+    ];
+    for (a, b, difference) in shape.iter() {
+        if a != b {
+            differences.push(difference.clone());
+        }
+    }
+    let same_shape = differences.is_empty();
+
+    if ours.security_level() != theirs.security_level() {
+        differences.push(ParamsDifference::SecurityLevel { ours: ours.security_level(), theirs: theirs.security_level() });
+    }
+    if !same_shape {
+        return differences;
+    }
+
+    if let Some(first_mismatch) = first_mismatch(&ours.round_constants, &theirs.round_constants) {
+        differences.push(ParamsDifference::RoundConstants { first_mismatch });
+    }
+    if let Some(first_mismatch) = first_mismatch(&ours.mds_matrix, &theirs.mds_matrix) {
+        differences.push(ParamsDifference::MdsMatrix { first_mismatch });
+    }
+
+    differences
+}
+
+fn first_mismatch(ours: &[bn256::Fr], theirs: &[bn256::Fr]) -> Option<usize> {
+    ours.iter().zip(theirs.iter()).position(|(a, b)| a != b)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::ff::{Field, PrimeField, from_hex};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::group_hash::Keccak256Hasher;
+    use crate::inverse::inverse_sbox;
+    use crate::{poseidon_hash, SBox};
+    use super::*;
+
+    #[test]
+    fn test_franklin_params_verify_seeds() {
+        let params = Bn256PoseidonParams::new_franklin_2_into_1::<Keccak256Hasher>();
+        assert!(params.verify_seeds::<Keccak256Hasher>());
+        assert!(!params.verify_seeds::<BlakeHasher>());
+    }
+
+    #[test]
+    fn test_franklin_params_match_franklin_crypto() {
+        // `Bn256RescueParams::new_checked_2_into_1()` of franklin-crypto 0.1.0,
+        // that has 135 round constants
+        let params = Bn256PoseidonParams::new_franklin_checked_2_into_1();
+        assert_eq!(params.round_constants[0], from_hex("2e827c42545887ff44c8e94bc429a6b9fba992c691596d3e97216a720f7b48ac").unwrap());
+        assert_eq!(params.round_constants[134], from_hex("164ed456b62365fbf715619f80da62933b86aed7522914928a7cfdc1ba3d7645").unwrap());
+
+        let mds_matrix = [
+            "2bec9c48301bdaaad9c72a3d8c4d363ac8be8189ccea49a4c0bd8c37d1c6b3e3",
+            "1f069c8a0ff22a1e62469e5f812fe7aca34de4c88515d84c9a1af9e21f5121a8",
+            "0d5365c702a1d156ecb069c1a5b6f3fefa94d552d01f19e0cea4ba91e4537c0b",
+            "1e4038a58dfdab6147e951763747a13f5e5599737bfa3c30d4548a366740959e",
+            "07cf3d4093243419a52e08fa9416458849e98b266a981933f96649f53f7824e3",
+            "116b9e8295f0086e020f035edc4ec2b5e65187597edb3d37de19589968b8d95a",
+            "1e2a87b6b95d6c53a4ef91c0f455ebda2d3b80bff560df8afc804813c15542fb",
+            "16e10361cbf35fe8e9c30527df038594e611b03897f65996a6986e51cfe3a96b",
+            "1c794b13d4b66d9883849539f556b020117666918dae1210cc4c713c4137c980",
+        ];
+        let mds_matrix: Vec<Fr> = mds_matrix.iter().map(|x| from_hex(x).unwrap()).collect();
+        assert_eq!(params.mds_matrix, mds_matrix);
+
+        // `rescue_hash(&[0, 1])` of franklin-crypto 0.1.0 with the same params
+        let expected = from_hex("0e233229d95c8e2fb8014517a4f90830929d60c32fdddccc83f2706ba9a8c644").unwrap();
+        assert_eq!(franklin_rescue_hash(&params, &[Fr::zero(), Fr::one()]), expected);
+    }
+
+    // transcription of `rescue_hash` of franklin-crypto for 22 rounds, an input
+    // of one block and the length in the capacity element
+    fn franklin_rescue_hash(params: &Bn256PoseidonParams, input: &[Fr]) -> Fr {
+        let t = params.state_width() as usize;
+        assert_eq!(input.len(), params.rate() as usize);
+        let inverse_sbox = inverse_sbox::<Bn256, _>(params.sbox()).unwrap();
+
+        let mut state = vec![Fr::zero(); t];
+        state[..input.len()].copy_from_slice(input);
+        state[t - 1] = Fr::from_str(&input.len().to_string()).unwrap();
+        for (s, c) in state.iter_mut().zip(params.round_constants(0)) {
+            s.add_assign(c);
+        }
+        for round in 0..44 {
+            if round % 2 == 0 {
+                inverse_sbox.apply(&mut state);
+            } else {
+                params.sbox().apply(&mut state);
+            }
+            let mut next = params.round_constants(round + 1).to_vec();
+            for (row, n) in next.iter_mut().enumerate() {
+                for (s, m) in state.iter().zip(params.mds_matrix_row(row as u32)) {
+                    let mut term = *s;
+                    term.mul_assign(m);
+                    n.add_assign(&term);
+                }
+            }
+            state = next;
+        }
+
+        state[0]
+    }
+
+    #[test]
+    fn test_franklin_params_differences() {
+        let ours = Bn256PoseidonParams::new_checked_2_into_1();
+        let legacy = Bn256PoseidonParams::new_franklin_checked_2_into_1();
+        assert!(params_differences(&legacy, &legacy).is_empty());
+        assert_eq!(params_differences(&ours, &legacy), vec![ParamsDifference::MdsMatrix { first_mismatch: 0 }]);
+        assert!(poseidon_hash::<Bn256>(&ours, &[Fr::zero(), Fr::one()]) != poseidon_hash::<Bn256>(&legacy, &[Fr::zero(), Fr::one()]));
+
+        let wider = Bn256PoseidonParams::new_franklin_3_into_1::<BlakeHasher>();
+        assert_eq!(params_differences(&legacy, &wider), vec![ParamsDifference::Rate { ours: 2, theirs: 3 }]);
+    }
+}
@@ -1,12 +1,27 @@
 use pairing::bn256;
-use pairing::ff::{Field, PrimeField, PrimeFieldRepr};
 use super::{PoseidonEngine, PoseidonHashParams, PoseidonParamsInternal, QuinticSBox, generate_mds_matrix};
 use super::group_hash::{GroupHasher, BlakeHasher};
+use super::sampling::{ConstantSampling, ParamsSeeds};
+
+mod builder;
+pub use self::builder::PoseidonParamsBuilder;
+pub mod legacy;
+pub mod evm;
 
 impl PoseidonEngine for bn256::Bn256 {
     type Params = Bn256PoseidonParams;
 }
 
+#[cfg(feature = "digest")]
+impl crate::digest::PoseidonDigestEngine for bn256::Bn256 {
+    type OutputSize = crate::digest_crate::generic_array::typenum::U32;
+
+    fn digest_params() -> &'static Self::Params {
+        static PARAMS: std::sync::OnceLock<Bn256PoseidonParams> = std::sync::OnceLock::new();
+        PARAMS.get_or_init(Bn256PoseidonParams::new_checked_2_into_1)
+    }
+}
+
 #[derive(Clone)]
 pub struct Bn256PoseidonParams {
     pub(crate) c: u32,
@@ -17,6 +32,8 @@ pub struct Bn256PoseidonParams {
     pub(crate) mds_matrix: Vec<bn256::Fr>,
     pub(crate) security_level: u32,
     pub(crate) sbox: QuinticSBox<bn256::Bn256>,
+    pub(crate) seeds: ParamsSeeds,
+    pub(crate) hasher: &'static str,
 }
 
 impl Bn256PoseidonParams {
@@ -51,82 +68,74 @@ impl Bn256PoseidonParams {
     }
 
     pub fn new_for_params<H: GroupHasher>(c: u32, r: u32, partial_rounds: u32, full_rounds: u32, security_level: u32) -> Self {
-        use byteorder::{WriteBytesExt, ReadBytesExt, BigEndian};
-        use super::constants;
+        Self::new_for_params_with_sampling::<H>(c, r, partial_rounds, full_rounds, security_level, ConstantSampling::Rejection)
+    }
+
+    pub fn new_for_params_with_sampling<H: GroupHasher>(
+        c: u32,
+        r: u32,
+        partial_rounds: u32,
+        full_rounds: u32,
+        security_level: u32,
+        sampling: ConstantSampling
+    ) -> Self {
+        let seeds = ParamsSeeds::default().with_sampling(sampling);
+
+        Self::new_with_seeds::<H>(c, r, partial_rounds, full_rounds, security_level, seeds)
+    }
 
+    /// Generates params from custom seeds, the seeds and the hasher name are
+    /// recorded in the params, so they can be regenerated with `verify_seeds`
+    pub fn new_with_seeds<H: GroupHasher>(
+        c: u32,
+        r: u32,
+        partial_rounds: u32,
+        full_rounds: u32,
+        security_level: u32,
+        seeds: ParamsSeeds
+    ) -> Self {
         let state_width = c + r;
         let num_round_constants = (full_rounds + partial_rounds) * state_width;
         let num_round_constants = num_round_constants as usize;
 
         // generate round constants based on some seed and hashing
-        let round_constants = {
-            let tag = b"Rescue_f";
-            let mut round_constants = Vec::with_capacity(num_round_constants);
-            let mut nonce = 0u32;
-            let mut nonce_bytes = [0u8; 4];
-
-            loop {
-                (&mut nonce_bytes[0..4]).write_u32::<BigEndian>(nonce).unwrap();
-                let mut h = H::new(&tag[..]);
-                h.update(constants::GH_FIRST_BLOCK);
-                h.update(&nonce_bytes[..]);
-                let h = h.finalize();
-                assert!(h.len() == 32);
-
-                let mut constant_repr = <bn256::Fr as PrimeField>::Repr::default();
-                constant_repr.read_le(&h[..]).unwrap();
-
-                if let Ok(constant) = bn256::Fr::from_repr(constant_repr) {
-                    if !constant.is_zero() {
-                        round_constants.push(constant);
-                    }
-                }
-
-                if round_constants.len() == num_round_constants {
-                    break;
-                }
-
-                nonce += 1;
-            }
-
-            round_constants
-        };
-
-        let mds_matrix = {
-            use rand::{SeedableRng};
-            use rand::chacha::ChaChaRng;
-            // Create an RNG based on the outcome of the random beacon
-            let mut rng = {
-                // This tag is a first one in a sequence of b"ResMxxxx"
-                // that produces MDS matrix without eigenvalues
-                // if we use Blake hasher
-                let tag = b"ResM0003";
-                let mut h = H::new(&tag[..]);
-                h.update(constants::GH_FIRST_BLOCK);
-                let h = h.finalize();
-                assert!(h.len() == 32);
-                let mut seed = [0u32; 8];
-                for (i, chunk) in h.chunks_exact(4).enumerate() {
-                    seed[i] = (&chunk[..]).read_u32::<BigEndian>().expect("digest is large enough for this to work");
-                }
-
-                ChaChaRng::from_seed(&seed)
-            };
-
-            generate_mds_matrix::<bn256::Bn256, _>(state_width, &mut rng)
-        };
+        let round_constants = seeds.sample_round_constants::<bn256::Fr, H>(num_round_constants);
+
+        // Create an RNG based on the outcome of the random beacon
+        let mds_matrix = generate_mds_matrix::<bn256::Bn256, _>(state_width, &mut seeds.mds_rng::<H>());
 
         Self {
-            c: c,
-            r: r,
+            c,
+            r,
             full_rounds,
             partial_rounds,
-            round_constants: round_constants,
-            mds_matrix: mds_matrix,
-            security_level: security_level,
+            round_constants,
+            mds_matrix,
+            security_level,
             sbox: QuinticSBox { _marker: std::marker::PhantomData },
+            seeds,
+            hasher: H::name(),
         }
     }
+
+    pub fn seeds(&self) -> &ParamsSeeds {
+        &self.seeds
+    }
+
+    pub fn hasher_name(&self) -> &'static str {
+        self.hasher
+    }
+
+    /// Regenerates the params from the recorded seeds and checks that
+    /// they are the same
+    pub fn verify_seeds<H: GroupHasher>(&self) -> bool {
+        if H::name() != self.hasher {
+            return false;
+        }
+        let regenerated = Self::new_with_seeds::<H>(self.c, self.r, self.partial_rounds, self.full_rounds, self.security_level, self.seeds.clone());
+
+        regenerated.round_constants == self.round_constants && regenerated.mds_matrix == self.mds_matrix
+    }
 }
 
 impl PoseidonParamsInternal<bn256::Bn256> for Bn256PoseidonParams {
@@ -187,6 +196,7 @@ impl PoseidonHashParams<bn256::Bn256> for Bn256PoseidonParams {
 #[cfg(test)]
 mod test {
     use rand::{Rng, thread_rng};
+    use crate::pairing::ff::{Field, PrimeField};
     use crate::pairing::bn256::{Bn256, Fr};
     use super::*;
     use crate::*;
@@ -218,6 +228,76 @@ mod test {
         let _ = stateful_rescue.squeeze_out_single();
     }
 
+    fn check_hasher_vectors<H: GroupHasher>(round_constant: &str, mds_element: &str, hash: &str) {
+        let params = Bn256PoseidonParams::new_2_into_1::<H>();
+        let output = poseidon_hash::<Bn256>(&params, &[Fr::zero(), Fr::one()]);
+
+        assert_eq!(format!("{}", params.round_constants(0)[0].into_repr()), round_constant);
+        assert_eq!(format!("{}", params.mds_matrix_row(0)[0].into_repr()), mds_element);
+        assert_eq!(format!("{}", output[0].into_repr()), hash);
+    }
+
+    #[test]
+    fn test_bn256_params_regression_vectors() {
+        use crate::group_hash::{Blake2bHasher, Keccak256Hasher, Sha256Hasher};
+
+        check_hasher_vectors::<BlakeHasher>(
+            "0x2e827c42545887ff44c8e94bc429a6b9fba992c691596d3e97216a720f7b48ac",
+            "0x05bb9226b9b9dd753cb5b4d591564d707a488ed2f9d742036b95e8f4436e174a",
+            "0x0449a3e07ef8552c4152a16804bfd7d00bdae417dfdfa441e07cec3fa5fc944a"
+        );
+        check_hasher_vectors::<Keccak256Hasher>(
+            "0x0ef30241d77becf4cb7b3b5d0dbeebb5ce97346dc2f83b0d314845fa67878c0e",
+            "0x1ed2e48fe05867dc5c4d43421f6bae83476dfd3c709cc91f7023cecce13a0db6",
+            "0x2aa6e299349e3af0d96be727dafa0433c076ce063ac3c30513b07a2caefe5e60"
+        );
+        check_hasher_vectors::<Blake2bHasher>(
+            "0x109b7ca930ea5316ed3090ba3282d0fe3e16c9c5d5053f8178c127c9199ef554",
+            "0x25b716ab4e6ff76835b633dae4511edd57554589570bfd760d3980f0826b499b",
+            "0x1b3ba046edf9d3bead0e194a4fef2c435a9766c265c6cfcac26edb42dd90d5d9"
+        );
+        check_hasher_vectors::<Sha256Hasher>(
+            "0x0155308ede0c7f49093c7cd22fce166422ad10f022bb5be831285a36f857085d",
+            "0x09763d25524b785692e1a093e846a53a1ea2ff5b1f6bc5305c56e849e5736745",
+            "0x11e7ea1a3c101495f1ee2e9e9e6f076367a035d048483094c6173700665a5cd6"
+        );
+    }
+
+    #[test]
+    fn test_bn256_wide_reduction_params() {
+        let legacy = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let params = Bn256PoseidonParams::new_for_params_with_sampling::<BlakeHasher>(1, 2, 83, 8, 126, ConstantSampling::WideReduction);
+        let output = poseidon_hash::<Bn256>(&params, &[Fr::zero(), Fr::one()]);
+
+        // only round constants differ
+        assert_eq!(params.mds_matrix, legacy.mds_matrix);
+        assert!(params.round_constants(0) != legacy.round_constants(0));
+        assert_eq!(format!("{}", output[0].into_repr()), "0x07b1fbb910281b3f6b848ee5bbb4edbb0ce340ac3a86fda204fcb366bad0997f");
+    }
+
+    #[test]
+    fn test_bn256_custom_seeds() {
+        use crate::group_hash::Keccak256Hasher;
+
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        assert_eq!(params.seeds(), &ParamsSeeds::default());
+        assert_eq!(params.hasher_name(), "blake2s");
+        assert!(params.verify_seeds::<BlakeHasher>());
+        assert!(!params.verify_seeds::<Keccak256Hasher>());
+
+        let seeds = ParamsSeeds::default().with_round_constants_tag(b"MyApp_rc").with_mds_tag(b"MyApp_m").with_first_block(b"application specific block");
+        let custom = Bn256PoseidonParams::new_with_seeds::<BlakeHasher>(1, 2, 83, 8, 126, seeds.clone());
+        assert_eq!(custom.seeds(), &seeds);
+        assert!(custom.verify_seeds::<BlakeHasher>());
+        assert!(custom.round_constants(0) != params.round_constants(0));
+        assert!(custom.mds_matrix_row(0) != params.mds_matrix_row(0));
+
+        // tampered params don't verify
+        let mut tampered = custom.clone();
+        tampered.round_constants[5] = Fr::one();
+        assert!(!tampered.verify_seeds::<BlakeHasher>());
+    }
+
     #[test]
     fn print_mds() {
         let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
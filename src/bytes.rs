@@ -0,0 +1,284 @@
+use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+use super::{PoseidonEngine, PoseidonHashParams, StatefulSponge};
+
+/// Describes how a byte string is split into field elements. Bytes are
+/// always consumed in order and bits inside of a byte from LSB to MSB,
+/// so `Bytes(n)` is a little-endian reading of `n`-byte chunks and `Bits`
+/// packs `F::CAPACITY` bits densely into every element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BytePacking {
+    Bytes(usize),
+    Bits,
+}
+
+impl BytePacking {
+    /// Largest whole number of bytes that fits into a field element,
+    /// e.g. 31 bytes for bn256
+    pub fn max_bytes_for_field<F: PrimeField>() -> Self {
+        BytePacking::Bytes((F::CAPACITY / 8) as usize)
+    }
+
+    pub fn bits_per_element<F: PrimeField>(&self) -> u32 {
+        match *self {
+            BytePacking::Bytes(n) => {
+                let bits = (n as u32) * 8;
+                assert!(bits > 0, "chunk must contain at least one byte");
+                assert!(bits <= F::CAPACITY, "chunk of {} bytes doesn't fit into the field", n);
+
+                bits
+            },
+            BytePacking::Bits => F::CAPACITY
+        }
+    }
+}
+
+#[derive(Clone)]
+struct FieldPacker<F: PrimeField> {
+    bits_per_element: u32,
+    filled: u32,
+    repr: F::Repr,
+}
+
+impl<F: PrimeField> FieldPacker<F> {
+    fn new(packing: BytePacking) -> Self {
+        Self {
+            bits_per_element: packing.bits_per_element::<F>(),
+            filled: 0,
+            repr: F::Repr::default(),
+        }
+    }
+
+    fn push_byte(&mut self, byte: u8, into: &mut impl FnMut(F)) {
+        for i in 0..8 {
+            if (byte >> i) & 1 == 1 {
+                let limbs = self.repr.as_mut();
+                limbs[(self.filled / 64) as usize] |= 1u64 << (self.filled % 64);
+            }
+            self.filled += 1;
+
+            if self.filled == self.bits_per_element {
+                into(self.take());
+            }
+        }
+    }
+
+    fn finalize(&mut self, into: &mut impl FnMut(F)) {
+        if self.filled != 0 {
+            into(self.take());
+        }
+    }
+
+    fn take(&mut self) -> F {
+        let repr = std::mem::take(&mut self.repr);
+        self.filled = 0;
+
+        F::from_repr(repr).expect("packed value is below the field capacity")
+    }
+}
+
+/// Packs bytes into field elements exactly the way the byte hashers do
+/// (without domain and length elements). The last element is zero-padded.
+pub fn pack_bytes<F: PrimeField>(
+    packing: BytePacking,
+    input: &[u8]
+) -> Vec<F> {
+    let mut result = vec![];
+    let mut packer = FieldPacker::<F>::new(packing);
+    let mut push = |el| result.push(el);
+    for &b in input.iter() {
+        packer.push_byte(b, &mut push);
+    }
+    packer.finalize(&mut push);
+
+    result
+}
+
+/// Encodes a domain tag of less than `F::CAPACITY / 8` bytes as a single
+/// element. The tag is zero-padded and followed by its length in the last
+/// byte that fits into the field, so tags that differ in trailing zeroes
+/// don't collide
+pub fn domain_to_field<F: PrimeField>(domain: &[u8]) -> F {
+    let length_position = (F::CAPACITY / 8) as usize - 1;
+    assert!(domain.len() <= length_position, "domain tag is too long");
+    let mut bytes = vec![0u8; F::Repr::default().as_ref().len() * 8];
+    bytes[..domain.len()].copy_from_slice(domain);
+    bytes[length_position] = domain.len() as u8;
+
+    let mut repr = F::Repr::default();
+    repr.read_le(&bytes[..]).unwrap();
+
+    F::from_repr(repr).expect("domain tag is below the field capacity")
+}
+
+/// Streaming byte hasher. The sponge absorbs the domain tag element, then
+/// the packed input and finally the input length in bytes, so
+/// messages are uniquely encoded even if the last chunk is partial.
+pub struct PoseidonByteWriter<'a, E: PoseidonEngine> {
+    sponge: StatefulSponge<'a, E>,
+    packer: FieldPacker<E::Fr>,
+    length: u64,
+}
+
+impl<'a, E: PoseidonEngine> Clone for PoseidonByteWriter<'a, E> {
+    fn clone(&self) -> Self {
+        Self {
+            sponge: self.sponge.clone(),
+            packer: self.packer.clone(),
+            length: self.length
+        }
+    }
+}
+
+impl<'a, E: PoseidonEngine> PoseidonByteWriter<'a, E> {
+    pub fn new(
+        params: &'a E::Params,
+        domain: &[u8],
+        packing: BytePacking
+    ) -> Self {
+        let mut sponge = StatefulSponge::<E>::new(params);
+        sponge.absorb_single_value(domain_to_field::<E::Fr>(domain));
+
+        Self {
+            sponge,
+            packer: FieldPacker::new(packing),
+            length: 0,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        input: &[u8]
+    ) {
+        let sponge = &mut self.sponge;
+        let mut absorb = |el| sponge.absorb_single_value(el);
+        for &b in input.iter() {
+            self.packer.push_byte(b, &mut absorb);
+        }
+        self.length += input.len() as u64;
+    }
+
+    pub fn finalize(
+        mut self
+    ) -> Vec<E::Fr> {
+        let sponge = &mut self.sponge;
+        self.packer.finalize(&mut |el| sponge.absorb_single_value(el));
+
+        let length = E::Fr::from_repr(<E::Fr as PrimeField>::Repr::from(self.length)).unwrap();
+        self.sponge.absorb_single_value(length);
+
+        let capacity = self.sponge.params.capacity();
+        let mut output = Vec::with_capacity(capacity as usize);
+        for _ in 0..capacity {
+            output.push(self.sponge.squeeze_out_single());
+        }
+
+        output
+    }
+}
+
+impl<'a, E: PoseidonEngine> std::io::Write for PoseidonByteWriter<'a, E> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn poseidon_hash_bytes<E: PoseidonEngine>(
+    params: &E::Params,
+    domain: &[u8],
+    packing: BytePacking,
+    input: &[u8]
+) -> Vec<E::Fr> {
+    let mut writer = PoseidonByteWriter::<E>::new(params, domain, packing);
+    writer.update(input);
+
+    writer.finalize()
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use std::io::Write;
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use super::*;
+
+    #[test]
+    fn test_bn256_default_packing() {
+        assert_eq!(BytePacking::max_bytes_for_field::<Fr>(), BytePacking::Bytes(31));
+
+        let input: Vec<u8> = (0..40).collect();
+        let packed = pack_bytes::<Fr>(BytePacking::Bytes(31), &input);
+        assert_eq!(packed.len(), 2);
+
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        let mut buf = input[31..].to_vec();
+        buf.resize(32, 0);
+        repr.read_le(&buf[..]).unwrap();
+        assert_eq!(packed[1], Fr::from_repr(repr).unwrap());
+
+        let packed = pack_bytes::<Fr>(BytePacking::Bits, &input);
+        assert_eq!(packed.len(), 2);
+    }
+
+    #[test]
+    fn test_bn256_bytes_hash_matches_field_hash() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let input: Vec<u8> = (0..100).map(|_| rng.gen()).collect();
+
+        let mut encoded = vec![domain_to_field::<Fr>(b"test")];
+        encoded.extend(pack_bytes::<Fr>(BytePacking::Bytes(31), &input));
+        encoded.push(Fr::from_repr(<Fr as PrimeField>::Repr::from(100)).unwrap());
+
+        let mut sponge = StatefulSponge::<Bn256>::new(&params);
+        for el in encoded.into_iter() {
+            sponge.absorb_single_value(el);
+        }
+
+        let output = poseidon_hash_bytes::<Bn256>(&params, b"test", BytePacking::Bytes(31), &input);
+        assert_eq!(output, vec![sponge.squeeze_out_single()]);
+    }
+
+    #[test]
+    fn test_bn256_streaming_bytes_hash() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+
+        for packing in [BytePacking::Bytes(31), BytePacking::Bytes(8), BytePacking::Bits].iter() {
+            let input: Vec<u8> = (0..257).map(|_| rng.gen()).collect();
+            let expected = poseidon_hash_bytes::<Bn256>(&params, b"stream", *packing, &input);
+
+            let mut writer = PoseidonByteWriter::<Bn256>::new(&params, b"stream", *packing);
+            for chunk in input.chunks(13) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.finalize(), expected);
+
+            // trailing zero byte must change the digest
+            let mut extended = input.clone();
+            extended.push(0);
+            let other = poseidon_hash_bytes::<Bn256>(&params, b"stream", *packing, &extended);
+            assert!(other != expected);
+        }
+    }
+
+    #[test]
+    fn test_bn256_domain_tag_encoding() {
+        assert!(domain_to_field::<Fr>(b"ab") != domain_to_field::<Fr>(b"ab\0"));
+        assert!(domain_to_field::<Fr>(b"") != domain_to_field::<Fr>(b"\0"));
+
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        let mut bytes = [0u8; 32];
+        bytes[..2].copy_from_slice(b"ab");
+        bytes[30] = 2;
+        repr.read_le(&bytes[..]).unwrap();
+        assert_eq!(domain_to_field::<Fr>(b"ab"), Fr::from_repr(repr).unwrap());
+    }
+}
@@ -0,0 +1,195 @@
+use super::{PoseidonEngine, PoseidonHashParams, small_sbox_exponent};
+use crate::plonk::{PoseidonPlonkLayout, SBoxGate};
+
+/// Gate sets for which PLONK gate counts are reported
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlonkGateSet {
+    /// Vanilla `q_l a + q_r b + q_o c + q_m a b + q_c = 0` gate
+    Width3Arithmetic,
+    /// Width-4 main gate with a single multiplication and no custom gates
+    Width4Arithmetic,
+    /// Width-4 main gate plus an `a^alpha = b` custom gate
+    Width4PowGate,
+    /// Width-4 gate with `alpha` powers of the inputs fused into the linear combination
+    Width4Fused,
+}
+
+impl PlonkGateSet {
+    pub const ALL: [PlonkGateSet; 4] = [
+        PlonkGateSet::Width3Arithmetic,
+        PlonkGateSet::Width4Arithmetic,
+        PlonkGateSet::Width4PowGate,
+        PlonkGateSet::Width4Fused,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlonkGateSet::Width3Arithmetic => "w3 arith",
+            PlonkGateSet::Width4Arithmetic => "w4 arith",
+            PlonkGateSet::Width4PowGate => "w4 pow",
+            PlonkGateSet::Width4Fused => "w4 fused",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostEstimate {
+    pub permutations: usize,
+    pub sboxes: usize,
+    pub r1cs_constraints: usize,
+    pub native_multiplications: usize,
+    pub plonk_gates: Vec<(PlonkGateSet, usize)>,
+}
+
+impl CostEstimate {
+    pub fn plonk_gates(&self, gate_set: PlonkGateSet) -> usize {
+        self.plonk_gates.iter().find(|(set, _)| *set == gate_set).unwrap().1
+    }
+}
+
+/// Number of permutations `StatefulSponge` makes to absorb `input_len`
+/// elements and squeeze `output_len` elements
+pub fn sponge_permutations(rate: usize, input_len: usize, output_len: usize) -> usize {
+    assert!(output_len > 0);
+    // the last absorbtion cycle is padded and permuted on the first squeeze
+    let absorbtion_cycles = input_len.div_ceil(rate).max(1);
+
+    absorbtion_cycles + (output_len - 1) / rate
+}
+
+// multiplications in the square-and-multiply chain of `x^alpha`
+fn sbox_multiplications(alpha: u64) -> usize {
+    assert!(alpha > 1);
+    let bits = 64 - alpha.leading_zeros() as usize;
+
+    (bits - 1) + (alpha.count_ones() as usize - 1)
+}
+
+// rows to compute a linear combination of `terms` elements, first row takes
+// `fan_in` terms and every next one takes `fan_in - 1` plus the accumulator
+fn linear_combination_rows(terms: usize, fan_in: usize) -> usize {
+    if terms <= fan_in {
+        1
+    } else {
+        1 + (terms - 2) / (fan_in - 1)
+    }
+}
+
+/// Cost of a single permutation
+pub fn permutation_cost<E: PoseidonEngine>(
+    params: &E::Params
+) -> CostEstimate {
+    let alpha = small_sbox_exponent::<E, _>(params.sbox()).expect("sbox must be a low degree power map");
+    let width = params.state_width() as usize;
+    let num_full_rounds = params.num_full_rounds() as usize;
+    let num_partial_rounds = params.num_partial_rounds() as usize;
+    let num_rounds = num_full_rounds + num_partial_rounds;
+
+    let sboxes = width * num_full_rounds + num_partial_rounds;
+    let sbox_cost = sbox_multiplications(alpha);
+
+    // linear layers are free in R1CS, round constants of the first round are added
+    // in separate gates and every next round gets them from the previous linear layer
+    let arithmetic_gates = |fan_in: usize| {
+        width + num_rounds * width * linear_combination_rows(width, fan_in) + sboxes * sbox_cost
+    };
+
+    let plonk_gates = PlonkGateSet::ALL.iter().map(|&gate_set| {
+        let gates = match gate_set {
+            PlonkGateSet::Width3Arithmetic => arithmetic_gates(2),
+            PlonkGateSet::Width4Arithmetic => arithmetic_gates(3),
+            PlonkGateSet::Width4PowGate => PoseidonPlonkLayout::new::<E>(params, SBoxGate::Naive).gates.len(),
+            PlonkGateSet::Width4Fused => PoseidonPlonkLayout::new::<E>(params, SBoxGate::Fused).gates.len(),
+        };
+
+        (gate_set, gates)
+    }).collect();
+
+    CostEstimate {
+        permutations: 1,
+        sboxes,
+        r1cs_constraints: sboxes * sbox_cost,
+        native_multiplications: sboxes * sbox_cost + num_rounds * width * width,
+        plonk_gates,
+    }
+}
+
+/// Cost of `poseidon_hash` of `input_len` elements, which squeezes
+/// `capacity()` elements
+pub fn estimate_hash_cost<E: PoseidonEngine>(
+    params: &E::Params,
+    input_len: usize
+) -> CostEstimate {
+    let permutation = permutation_cost::<E>(params);
+    let permutations = sponge_permutations(params.rate() as usize, input_len, params.capacity() as usize);
+
+    CostEstimate {
+        permutations,
+        sboxes: permutations * permutation.sboxes,
+        r1cs_constraints: permutations * permutation.r1cs_constraints,
+        native_multiplications: permutations * permutation.native_multiplications,
+        plonk_gates: permutation.plonk_gates.into_iter().map(|(set, gates)| (set, permutations * gates)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::bn256::Bn256;
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use super::*;
+
+    #[test]
+    fn test_sponge_permutations() {
+        assert_eq!(sponge_permutations(2, 0, 1), 1);
+        assert_eq!(sponge_permutations(2, 2, 1), 1);
+        assert_eq!(sponge_permutations(2, 3, 1), 2);
+        assert_eq!(sponge_permutations(2, 4, 3), 3);
+        assert_eq!(sponge_permutations(4, 4, 2), 1);
+        assert_eq!(sbox_multiplications(5), 3);
+        assert_eq!(sbox_multiplications(7), 4);
+    }
+
+    #[test]
+    fn test_bn256_cost_estimates() {
+        let params = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 33, 8, 80);
+        let cost = permutation_cost::<Bn256>(&params);
+        assert_eq!(cost.sboxes, 3 * 8 + 33);
+        assert_eq!(cost.r1cs_constraints, 3 * (3 * 8 + 33));
+        assert_eq!(cost.native_multiplications, 3 * (3 * 8 + 33) + 9 * 41);
+        assert_eq!(cost.plonk_gates(PlonkGateSet::Width4Fused), 3 + 3 * 41);
+        assert_eq!(cost.plonk_gates(PlonkGateSet::Width4PowGate), 3 + 3 * 41 + cost.sboxes);
+        assert_eq!(cost.plonk_gates(PlonkGateSet::Width4Arithmetic), 3 + 3 * 41 + cost.r1cs_constraints);
+        assert_eq!(cost.plonk_gates(PlonkGateSet::Width3Arithmetic), 3 + 2 * 3 * 41 + cost.r1cs_constraints);
+
+        let hash_cost = estimate_hash_cost::<Bn256>(&params, 5);
+        assert_eq!(hash_cost.permutations, 3);
+        assert_eq!(hash_cost.r1cs_constraints, 3 * cost.r1cs_constraints);
+
+        // the generic width-4 estimate matches the actual layout for wide states
+        let wide = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 8, 10, 8, 80);
+        let cost = permutation_cost::<Bn256>(&wide);
+        let linear_rows = cost.plonk_gates(PlonkGateSet::Width4PowGate) - cost.sboxes;
+        assert_eq!(cost.plonk_gates(PlonkGateSet::Width4Arithmetic) - cost.r1cs_constraints, linear_rows);
+    }
+
+    #[test]
+    fn test_hash_cost_counts_squeezed_elements() {
+        use crate::pairing::ff::Field;
+        use crate::pasta::{Fp, PallasBase, PastaPoseidonParams};
+        use crate::poseidon_hash;
+
+        // same width with a larger capacity, `poseidon_hash` squeezes two
+        // elements while the output length stays one
+        let mut params = PastaPoseidonParams::<PallasBase>::new_p128_pow5_t3();
+        params.c = 2;
+        params.r = 1;
+        assert_eq!(params.output_len(), 1);
+        assert_eq!(poseidon_hash::<PallasBase>(&params, &[Fp::one()]).len(), 2);
+
+        let cost = permutation_cost::<PallasBase>(&params);
+        let hash_cost = estimate_hash_cost::<PallasBase>(&params, 1);
+        assert_eq!(hash_cost.permutations, 2);
+        assert_eq!(hash_cost.sboxes, 2 * cost.sboxes);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::digest_crate::{Update, FixedOutput, Reset};
+use crate::digest_crate::generic_array::{ArrayLength, GenericArray};
+use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+
+use super::PoseidonEngine;
+use super::bytes::{BytePacking, PoseidonByteWriter};
+
+/// Domain tag absorbed before the message by `PoseidonDigest`
+pub const DIGEST_DOMAIN: &[u8] = b"PoseidonDigest";
+
+/// Engines that have a fixed set of params to be used by `PoseidonDigest`.
+/// `Default` can not take params as an argument, so they have to live
+/// for the whole program run.
+pub trait PoseidonDigestEngine: PoseidonEngine {
+    /// Byte length of the little-endian encoding of `Self::Fr`
+    type OutputSize: ArrayLength<u8>;
+
+    fn digest_params() -> &'static Self::Params;
+}
+
+/// Byte oriented hasher that implements RustCrypto traits on top of
+/// `StatefulSponge`. Input is packed with `BytePacking::max_bytes_for_field`
+/// under the `DIGEST_DOMAIN` tag and the output is the first squeezed
+/// element in canonical little-endian form.
+pub struct PoseidonDigest<E: PoseidonDigestEngine> {
+    writer: PoseidonByteWriter<'static, E>
+}
+
+impl<E: PoseidonDigestEngine> PoseidonDigest<E> {
+    fn new_writer() -> PoseidonByteWriter<'static, E> {
+        PoseidonByteWriter::new(E::digest_params(), DIGEST_DOMAIN, BytePacking::max_bytes_for_field::<E::Fr>())
+    }
+
+    fn output_into(writer: PoseidonByteWriter<'static, E>, out: &mut GenericArray<u8, E::OutputSize>) {
+        let output = writer.finalize()[0];
+        output.into_repr().write_le(&mut out[..]).expect("output size must match the field encoding");
+    }
+}
+
+impl<E: PoseidonDigestEngine> Default for PoseidonDigest<E> {
+    fn default() -> Self {
+        Self {
+            writer: Self::new_writer()
+        }
+    }
+}
+
+impl<E: PoseidonDigestEngine> Clone for PoseidonDigest<E> {
+    fn clone(&self) -> Self {
+        Self {
+            writer: self.writer.clone()
+        }
+    }
+}
+
+impl<E: PoseidonDigestEngine> Update for PoseidonDigest<E> {
+    fn update(&mut self, data: impl AsRef<[u8]>) {
+        self.writer.update(data.as_ref());
+    }
+}
+
+impl<E: PoseidonDigestEngine> FixedOutput for PoseidonDigest<E> {
+    type OutputSize = E::OutputSize;
+
+    fn finalize_into(self, out: &mut GenericArray<u8, Self::OutputSize>) {
+        Self::output_into(self.writer, out);
+    }
+
+    fn finalize_into_reset(&mut self, out: &mut GenericArray<u8, Self::OutputSize>) {
+        let writer = std::mem::replace(&mut self.writer, Self::new_writer());
+        Self::output_into(writer, out);
+    }
+}
+
+impl<E: PoseidonDigestEngine> Reset for PoseidonDigest<E> {
+    fn reset(&mut self) {
+        self.writer = Self::new_writer();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::digest_crate::Digest;
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+    use crate::bytes::{BytePacking, poseidon_hash_bytes};
+    use super::{PoseidonDigest, PoseidonDigestEngine, DIGEST_DOMAIN};
+
+    fn hash_generic<D: Digest>(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut hasher = D::new();
+        for chunk in chunks.iter() {
+            hasher.update(chunk);
+        }
+
+        hasher.finalize().to_vec()
+    }
+
+    #[test]
+    fn test_bn256_digest_matches_bytes_hash() {
+        let rng = &mut thread_rng();
+        let input: Vec<u8> = (0..77).map(|_| rng.gen()).collect();
+
+        let expected = poseidon_hash_bytes::<Bn256>(
+            Bn256::digest_params(),
+            DIGEST_DOMAIN,
+            BytePacking::max_bytes_for_field::<Fr>(),
+            &input
+        )[0];
+        let mut expected_bytes = vec![];
+        expected.into_repr().write_le(&mut expected_bytes).unwrap();
+
+        let output = hash_generic::<PoseidonDigest<Bn256>>(&[&input[..10], &input[10..]]);
+        assert_eq!(output, expected_bytes);
+        assert_eq!(PoseidonDigest::<Bn256>::output_size(), 32);
+    }
+
+    #[test]
+    fn test_bn256_digest_reset() {
+        let mut hasher = PoseidonDigest::<Bn256>::new();
+        hasher.update(b"some garbage");
+        hasher.reset();
+        hasher.update(b"message");
+        let first = hasher.finalize_reset();
+
+        hasher.update(b"message");
+        let second = hasher.finalize();
+
+        assert_eq!(first, second);
+        assert_eq!(first, PoseidonDigest::<Bn256>::digest(b"message"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// Invalid parameters or inputs that don't match the parameters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamsError {
+    ZeroRate,
+    ZeroCapacity,
+    OddNumberOfFullRounds(u32),
+    NoRounds,
+    /// `compression_rate` would be zero
+    RateBelowOutputLength { rate: u32, output_len: u32 },
+    InvalidStateLength { expected: usize, actual: usize },
+    /// Params don't fit a sponge specialized for a fixed rate and capacity
+    ShapeMismatch { expected_rate: usize, expected_capacity: usize, rate: u32, capacity: u32 },
+    /// Generated code for a fixed number of inputs needs at least one
+    NoInputs,
+    /// Tags of the hashers are at most `group_hash::PERSONALIZATION_LEN` bytes
+    PersonalizationTooLong { len: usize },
+    /// Security level must be positive and at most half of the capacity in bits
+    InvalidSecurityLevel { security_level: u32, max: u32 },
+    /// Generated contract is larger than the chain accepts
+    CodeSizeLimitExceeded { size: usize, limit: usize },
+    /// Exponent of an imported config doesn't match the sbox of the params
+    UnsupportedAlpha(u64),
+    /// Number of round constants or MDS size doesn't match the shape
+    MalformedConstants,
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamsError::ZeroRate => write!(f, "rate must be non-zero"),
+            ParamsError::ZeroCapacity => write!(f, "capacity must be non-zero"),
+            ParamsError::OddNumberOfFullRounds(rounds) => write!(f, "number of full rounds must be even, got {}", rounds),
+            ParamsError::NoRounds => write!(f, "permutation must have at least one round"),
+            ParamsError::RateBelowOutputLength { rate, output_len } => {
+                write!(f, "rate {} is less than the output length {}", rate, output_len)
+            },
+            ParamsError::InvalidStateLength { expected, actual } => {
+                write!(f, "state length must be {}, got {}", expected, actual)
+            },
+            ParamsError::ShapeMismatch { expected_rate, expected_capacity, rate, capacity } => {
+                write!(f, "expected rate {} and capacity {}, got rate {} and capacity {}", expected_rate, expected_capacity, rate, capacity)
+            },
+            ParamsError::NoInputs => write!(f, "number of inputs must be non-zero"),
+            ParamsError::PersonalizationTooLong { len } => {
+                write!(f, "personalization of {} bytes is longer than {}", len, crate::group_hash::PERSONALIZATION_LEN)
+            },
+            ParamsError::InvalidSecurityLevel { security_level, max } => {
+                write!(f, "security level must be in 1..={}, got {}", max, security_level)
+            },
+            ParamsError::CodeSizeLimitExceeded { size, limit } => {
+                write!(f, "code of {} bytes exceeds the limit of {} bytes", size, limit)
+            },
+            ParamsError::UnsupportedAlpha(alpha) => write!(f, "sbox x^{} is not supported", alpha),
+            ParamsError::MalformedConstants => write!(f, "round constants or MDS don't match the shape"),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
@@ -0,0 +1,93 @@
+/// Just enough of `rand_chacha::ChaCha8Rng` (rand 0.8) to regenerate
+/// plonky2's round constants: `seed_from_u64` and `gen_range` over `u64`
+pub(crate) struct ChaCha8Rng {
+    key: [u32; 8],
+    counter: u64,
+    block: [u32; 16],
+    index: usize,
+}
+
+impl ChaCha8Rng {
+    /// Expands the seed with PCG32 as `rand_core::SeedableRng::seed_from_u64` does
+    pub(crate) fn seed_from_u64(mut state: u64) -> Self {
+        const MUL: u64 = 6364136223846793005;
+        const INC: u64 = 11634580027462260723;
+
+        let mut key = [0u32; 8];
+        for word in key.iter_mut() {
+            state = state.wrapping_mul(MUL).wrapping_add(INC);
+            let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+            let rot = (state >> 59) as u32;
+            *word = xorshifted.rotate_right(rot);
+        }
+
+        Self { key, counter: 0, block: [0u32; 16], index: 16 }
+    }
+
+    fn refill(&mut self) {
+        let mut initial = [0u32; 16];
+        initial[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+        initial[4..12].copy_from_slice(&self.key);
+        initial[12] = self.counter as u32;
+        initial[13] = (self.counter >> 32) as u32;
+
+        let mut state = initial;
+        for _ in 0..4 {
+            quarter_round(&mut state, 0, 4, 8, 12);
+            quarter_round(&mut state, 1, 5, 9, 13);
+            quarter_round(&mut state, 2, 6, 10, 14);
+            quarter_round(&mut state, 3, 7, 11, 15);
+            quarter_round(&mut state, 0, 5, 10, 15);
+            quarter_round(&mut state, 1, 6, 11, 12);
+            quarter_round(&mut state, 2, 7, 8, 13);
+            quarter_round(&mut state, 3, 4, 9, 14);
+        }
+        for (word, initial) in state.iter_mut().zip(initial.iter()) {
+            *word = word.wrapping_add(*initial);
+        }
+
+        self.block = state;
+        self.counter += 1;
+        self.index = 0;
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        if self.index == 16 {
+            self.refill();
+        }
+        let word = self.block[self.index];
+        self.index += 1;
+
+        word
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        let high = self.next_u32() as u64;
+
+        (high << 32) | low
+    }
+
+    /// Uniform value in `0..bound` by widening multiplication and rejection
+    pub(crate) fn gen_range(&mut self, bound: u64) -> u64 {
+        assert!(bound != 0);
+        let zone = (bound << bound.leading_zeros()).wrapping_sub(1);
+        loop {
+            let product = (self.next_u64() as u128) * (bound as u128);
+            if (product as u64) <= zone {
+                return (product >> 64) as u64;
+            }
+        }
+    }
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+use crate::pairing::ff::{Field, LegendreSymbol, PrimeField, PrimeFieldDecodingError, PrimeFieldRepr, SqrtField};
+use crate::rand::{Rand, Rng};
+
+/// `p = 2^64 - 2^32 + 1`
+const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+/// `2^64 mod p`
+const EPSILON: u64 = 0xffff_ffff;
+
+const GENERATOR: u64 = 7;
+
+/// `GENERATOR^((p - 1) / 2^32)`
+const ROOT_OF_UNITY: u64 = 0x1856_29dc_da58_878c;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct FrRepr(pub [u64; 1]);
+
+impl fmt::Debug for FrRepr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:016x}", self.0[0])
+    }
+}
+
+impl fmt::Display for FrRepr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:016x}", self.0[0])
+    }
+}
+
+impl Rand for FrRepr {
+    fn rand<R: Rng>(rng: &mut R) -> Self {
+        FrRepr([rng.gen()])
+    }
+}
+
+impl AsRef<[u64]> for FrRepr {
+    fn as_ref(&self) -> &[u64] {
+        &self.0
+    }
+}
+
+impl AsMut<[u64]> for FrRepr {
+    fn as_mut(&mut self) -> &mut [u64] {
+        &mut self.0
+    }
+}
+
+impl From<u64> for FrRepr {
+    fn from(value: u64) -> Self {
+        FrRepr([value])
+    }
+}
+
+impl From<Fr> for FrRepr {
+    fn from(value: Fr) -> Self {
+        value.into_repr()
+    }
+}
+
+impl PrimeFieldRepr for FrRepr {
+    fn sub_noborrow(&mut self, other: &Self) {
+        self.0[0] = self.0[0].wrapping_sub(other.0[0]);
+    }
+    fn add_nocarry(&mut self, other: &Self) {
+        self.0[0] = self.0[0].wrapping_add(other.0[0]);
+    }
+    fn num_bits(&self) -> u32 {
+        64 - self.0[0].leading_zeros()
+    }
+    fn is_zero(&self) -> bool {
+        self.0[0] == 0
+    }
+    fn is_odd(&self) -> bool {
+        self.0[0] & 1 == 1
+    }
+    fn is_even(&self) -> bool {
+        !self.is_odd()
+    }
+    fn div2(&mut self) {
+        self.0[0] >>= 1;
+    }
+    fn shr(&mut self, amt: u32) {
+        self.0[0] = self.0[0].checked_shr(amt).unwrap_or(0);
+    }
+    fn mul2(&mut self) {
+        self.0[0] <<= 1;
+    }
+    fn shl(&mut self, amt: u32) {
+        self.0[0] = self.0[0].checked_shl(amt).unwrap_or(0);
+    }
+}
+
+/// Goldilocks field element, stored in canonical form without Montgomery encoding
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Fr(u64);
+
+impl Fr {
+    pub const fn from_canonical_u64(value: u64) -> Self {
+        assert!(value < MODULUS);
+        Fr(value)
+    }
+
+    pub fn to_canonical_u64(&self) -> u64 {
+        self.0
+    }
+
+    pub(crate) fn char_u64() -> u64 {
+        MODULUS
+    }
+
+    /// Reduction specialized for the modulus: `2^64 = 2^32 - 1` and `2^96 = -1`
+    #[inline(always)]
+    fn reduce128(x: u128) -> Self {
+        let x_lo = x as u64;
+        let x_hi = (x >> 64) as u64;
+        let x_hi_hi = x_hi >> 32;
+        let x_hi_lo = x_hi & EPSILON;
+
+        let (mut t0, borrow) = x_lo.overflowing_sub(x_hi_hi);
+        if borrow {
+            // can't underflow as `x_lo - x_hi_hi + 2^64 >= 2^64 - 2^32`
+            t0 -= EPSILON;
+        }
+        let t1 = x_hi_lo * EPSILON;
+        let (mut t2, carry) = t0.overflowing_add(t1);
+        if carry {
+            // can't overflow as `t1 <= (2^32 - 1)^2`
+            t2 += EPSILON;
+        }
+        if t2 >= MODULUS {
+            t2 -= MODULUS;
+        }
+
+        Fr(t2)
+    }
+}
+
+impl fmt::Debug for Fr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fr({})", self.into_repr())
+    }
+}
+
+impl fmt::Display for Fr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fr({})", self.into_repr())
+    }
+}
+
+impl Rand for Fr {
+    fn rand<R: Rng>(rng: &mut R) -> Self {
+        loop {
+            let value: u64 = rng.gen();
+            if value < MODULUS {
+                return Fr(value);
+            }
+        }
+    }
+}
+
+impl Field for Fr {
+    fn zero() -> Self {
+        Fr(0)
+    }
+    fn one() -> Self {
+        Fr(1)
+    }
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+    fn square(&mut self) {
+        *self = Self::reduce128((self.0 as u128) * (self.0 as u128));
+    }
+    fn double(&mut self) {
+        let value = *self;
+        self.add_assign(&value);
+    }
+    fn negate(&mut self) {
+        if self.0 != 0 {
+            self.0 = MODULUS - self.0;
+        }
+    }
+    fn add_assign(&mut self, other: &Self) {
+        let (sum, carry) = self.0.overflowing_add(other.0);
+        self.0 = if carry {
+            // both are canonical, so this is below the modulus
+            sum + EPSILON
+        } else if sum >= MODULUS {
+            sum - MODULUS
+        } else {
+            sum
+        };
+    }
+    fn sub_assign(&mut self, other: &Self) {
+        let (difference, borrow) = self.0.overflowing_sub(other.0);
+        self.0 = if borrow {
+            difference.wrapping_sub(EPSILON)
+        } else {
+            difference
+        };
+    }
+    fn mul_assign(&mut self, other: &Self) {
+        *self = Self::reduce128((self.0 as u128) * (other.0 as u128));
+    }
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.pow([MODULUS - 2]))
+        }
+    }
+    fn frobenius_map(&mut self, _power: usize) {}
+}
+
+impl PrimeField for Fr {
+    type Repr = FrRepr;
+
+    const NUM_BITS: u32 = 64;
+    const CAPACITY: u32 = 63;
+    const S: u32 = 32;
+
+    fn from_repr(repr: FrRepr) -> Result<Self, PrimeFieldDecodingError> {
+        if repr.0[0] < MODULUS {
+            Ok(Fr(repr.0[0]))
+        } else {
+            Err(PrimeFieldDecodingError::NotInField(format!("{}", repr)))
+        }
+    }
+    fn from_raw_repr(repr: FrRepr) -> Result<Self, PrimeFieldDecodingError> {
+        Self::from_repr(repr)
+    }
+    fn into_repr(&self) -> FrRepr {
+        FrRepr([self.0])
+    }
+    fn into_raw_repr(&self) -> FrRepr {
+        self.into_repr()
+    }
+    fn char() -> FrRepr {
+        FrRepr([MODULUS])
+    }
+    fn multiplicative_generator() -> Self {
+        Fr(GENERATOR)
+    }
+    fn root_of_unity() -> Self {
+        Fr(ROOT_OF_UNITY)
+    }
+}
+
+impl SqrtField for Fr {
+    fn legendre(&self) -> LegendreSymbol {
+        let symbol = self.pow([(MODULUS - 1) >> 1]);
+        if symbol.is_zero() {
+            LegendreSymbol::Zero
+        } else if symbol == Self::one() {
+            LegendreSymbol::QuadraticResidue
+        } else {
+            LegendreSymbol::QuadraticNonResidue
+        }
+    }
+
+    // Tonelli-Shanks
+    fn sqrt(&self) -> Option<Self> {
+        match self.legendre() {
+            LegendreSymbol::Zero => Some(*self),
+            LegendreSymbol::QuadraticNonResidue => None,
+            LegendreSymbol::QuadraticResidue => {
+                let t_minus_one_over_two = (MODULUS >> Self::S) >> 1;
+                let mut c = Self::root_of_unity();
+                let mut r = self.pow([t_minus_one_over_two]);
+                let mut t = r;
+                t.square();
+                t.mul_assign(self);
+                r.mul_assign(self);
+                let mut m = Self::S;
+
+                while t != Self::one() {
+                    let mut i = 1;
+                    let mut t2i = t;
+                    t2i.square();
+                    while t2i != Self::one() {
+                        t2i.square();
+                        i += 1;
+                    }
+
+                    for _ in 0..(m - i - 1) {
+                        c.square();
+                    }
+                    r.mul_assign(&c);
+                    c.square();
+                    t.mul_assign(&c);
+                    m = i;
+                }
+
+                Some(r)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_bigint::BigUint;
+    use rand::{Rng, thread_rng};
+    use super::*;
+
+    #[test]
+    fn test_goldilocks_arithmetic_matches_bigint() {
+        let rng = &mut thread_rng();
+        let modulus = BigUint::from(MODULUS);
+        let edge = [0, 1, EPSILON, MODULUS - 1, MODULUS - 2, 1 << 32, (1 << 63) + 5];
+        let mut values: Vec<Fr> = edge.iter().map(|v| Fr::from_canonical_u64(*v)).collect();
+        values.extend((0..50).map(|_| rng.gen::<Fr>()));
+
+        for a in values.iter() {
+            for b in values.iter() {
+                let (x, y) = (BigUint::from(a.0), BigUint::from(b.0));
+
+                let mut sum = *a;
+                sum.add_assign(b);
+                assert_eq!(BigUint::from(sum.0), (&x + &y) % &modulus);
+
+                let mut difference = *a;
+                difference.sub_assign(b);
+                assert_eq!(BigUint::from(difference.0), (&x + &modulus - &y) % &modulus);
+
+                let mut product = *a;
+                product.mul_assign(b);
+                assert_eq!(BigUint::from(product.0), (&x * &y) % &modulus);
+            }
+        }
+    }
+
+    #[test]
+    fn test_goldilocks_field_constants() {
+        let rng = &mut thread_rng();
+        let root = Fr::root_of_unity();
+        assert_eq!(root.pow([1u64 << 32]), Fr::one());
+        assert!(root.pow([1u64 << 31]) != Fr::one());
+
+        for _ in 0..20 {
+            let a: Fr = rng.gen();
+            let mut inverse = a.inverse().unwrap();
+            inverse.mul_assign(&a);
+            assert_eq!(inverse, Fr::one());
+
+            let mut square = a;
+            square.square();
+            let mut root = square.sqrt().unwrap();
+            root.square();
+            assert_eq!(root, square);
+        }
+        assert_eq!(Fr::from_str("18446744069414584320").unwrap(), Fr::from_canonical_u64(MODULUS - 1));
+    }
+}
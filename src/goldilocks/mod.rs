@@ -0,0 +1,217 @@
+use crate::pairing::ff::{Field, ScalarEngine};
+use super::{PoseidonEngine, PoseidonHashParams, PoseidonParamsInternal, SepticSBox, poseidon_mimc_in_place};
+
+mod chacha;
+mod fr;
+pub use self::fr::{Fr, FrRepr};
+
+use self::chacha::ChaCha8Rng;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Goldilocks;
+
+impl ScalarEngine for Goldilocks {
+    type Fr = Fr;
+}
+
+impl PoseidonEngine for Goldilocks {
+    type Params = GoldilocksPoseidonParams;
+}
+
+pub const PLONKY2_WIDTH: usize = 12;
+pub const PLONKY2_RATE: usize = 8;
+pub const NUM_HASH_OUT_ELTS: usize = 4;
+
+/// plonky2's `HashOut`
+pub type HashOut = [Fr; NUM_HASH_OUT_ELTS];
+
+// first row of the circulant part and the diagonal of plonky2's MDS matrix
+const MDS_MATRIX_CIRC: [u64; PLONKY2_WIDTH] = [17, 15, 41, 16, 2, 28, 13, 13, 39, 18, 34, 20];
+const MDS_MATRIX_DIAG: [u64; PLONKY2_WIDTH] = [8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+#[derive(Clone)]
+pub struct GoldilocksPoseidonParams {
+    pub(crate) c: u32,
+    pub(crate) r: u32,
+    pub(crate) full_rounds: u32,
+    pub(crate) partial_rounds: u32,
+    pub(crate) round_constants: Vec<Fr>,
+    pub(crate) mds_matrix: Vec<Fr>,
+    pub(crate) security_level: u32,
+    pub(crate) sbox: SepticSBox<Goldilocks>,
+}
+
+impl GoldilocksPoseidonParams {
+    /// plonky2's `Poseidon` for `GoldilocksField`: width 12, rate 8, `x^7`,
+    /// 8 full and 22 partial rounds. Round constants are plonky2's
+    /// `ALL_ROUND_CONSTANTS`, drawn from `ChaCha8Rng::seed_from_u64(0)`
+    pub fn new_plonky2() -> Self {
+        let full_rounds = 8u32;
+        let partial_rounds = 22u32;
+        let num_constants = PLONKY2_WIDTH * (full_rounds + partial_rounds) as usize;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let modulus = Fr::char_u64();
+        let round_constants = (0..num_constants)
+            .map(|_| Fr::from_canonical_u64(rng.gen_range(modulus)))
+            .collect();
+
+        // row `r` is `v -> sum_i v[(i + r) % 12] * CIRC[i] + v[r] * DIAG[r]`
+        let mut mds_matrix = Vec::with_capacity(PLONKY2_WIDTH * PLONKY2_WIDTH);
+        for row in 0..PLONKY2_WIDTH {
+            for column in 0..PLONKY2_WIDTH {
+                let mut element = MDS_MATRIX_CIRC[(column + PLONKY2_WIDTH - row) % PLONKY2_WIDTH];
+                if row == column {
+                    element += MDS_MATRIX_DIAG[row];
+                }
+                mds_matrix.push(Fr::from_canonical_u64(element));
+            }
+        }
+
+        Self {
+            c: (PLONKY2_WIDTH - PLONKY2_RATE) as u32,
+            r: PLONKY2_RATE as u32,
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds_matrix,
+            security_level: 128,
+            sbox: SepticSBox { _marker: std::marker::PhantomData },
+        }
+    }
+}
+
+impl PoseidonParamsInternal<Goldilocks> for GoldilocksPoseidonParams {
+    fn set_round_constants(&mut self, to: Vec<Fr>) {
+        assert_eq!(self.round_constants.len(), to.len());
+        self.round_constants = to;
+    }
+}
+
+impl PoseidonHashParams<Goldilocks> for GoldilocksPoseidonParams {
+    type SBox = SepticSBox<Goldilocks>;
+
+    fn capacity(&self) -> u32 {
+        self.c
+    }
+    fn rate(&self) -> u32 {
+        self.r
+    }
+    fn num_full_rounds(&self) -> u32 {
+        self.full_rounds
+    }
+    fn num_partial_rounds(&self) -> u32 {
+        self.partial_rounds
+    }
+    fn round_constants(&self, round: u32) -> &[Fr] {
+        let t = self.c + self.r;
+        let start = (t*round) as usize;
+        let end = (t*(round+1)) as usize;
+
+        &self.round_constants[start..end]
+    }
+    fn mds_matrix_row(&self, row: u32) -> &[Fr] {
+        let t = self.c + self.r;
+        let start = (t*row) as usize;
+        let end = (t*(row+1)) as usize;
+
+        &self.mds_matrix[start..end]
+    }
+    fn security_level(&self) -> u32 {
+        self.security_level
+    }
+    fn output_len(&self) -> u32 {
+        NUM_HASH_OUT_ELTS as u32
+    }
+    // plonky2 applies partial round sbox to the first element
+    fn partial_round_sbox_index(&self) -> u32 {
+        0
+    }
+
+    fn sbox(&self) -> &Self::SBox {
+        &self.sbox
+    }
+}
+
+fn plonky2_params() -> &'static GoldilocksPoseidonParams {
+    static PARAMS: std::sync::OnceLock<GoldilocksPoseidonParams> = std::sync::OnceLock::new();
+    PARAMS.get_or_init(GoldilocksPoseidonParams::new_plonky2)
+}
+
+fn permute(state: &mut [Fr; PLONKY2_WIDTH]) {
+    let mut scratch = [Fr::zero(); PLONKY2_WIDTH];
+    poseidon_mimc_in_place::<Goldilocks>(plonky2_params(), state, &mut scratch);
+}
+
+/// plonky2's `hash_n_to_hash_no_pad`: every chunk of the input overwrites the
+/// rate part of the state before a permutation, an empty input hashes to zero
+pub fn hash_n_to_hash_no_pad(inputs: &[Fr]) -> HashOut {
+    let mut state = [Fr::zero(); PLONKY2_WIDTH];
+    for chunk in inputs.chunks(PLONKY2_RATE) {
+        state[..chunk.len()].copy_from_slice(chunk);
+        permute(&mut state);
+    }
+
+    let mut output = [Fr::zero(); NUM_HASH_OUT_ELTS];
+    output.copy_from_slice(&state[..NUM_HASH_OUT_ELTS]);
+
+    output
+}
+
+/// plonky2's `two_to_one` (`compress`) for Merkle trees
+pub fn two_to_one(left: HashOut, right: HashOut) -> HashOut {
+    let mut state = [Fr::zero(); PLONKY2_WIDTH];
+    state[..NUM_HASH_OUT_ELTS].copy_from_slice(&left);
+    state[NUM_HASH_OUT_ELTS..(2 * NUM_HASH_OUT_ELTS)].copy_from_slice(&right);
+    permute(&mut state);
+
+    let mut output = [Fr::zero(); NUM_HASH_OUT_ELTS];
+    output.copy_from_slice(&state[..NUM_HASH_OUT_ELTS]);
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn elements(values: &[u64]) -> Vec<Fr> {
+        values.iter().map(|v| Fr::from_canonical_u64(*v)).collect()
+    }
+
+    #[test]
+    fn test_plonky2_permutation_vectors() {
+        let params = plonky2_params();
+        assert_eq!(params.round_constants[..4], elements(&[0xb585f766f2144405, 0x7746a55f43921ad7, 0xb2fb0d31cee799b4, 0x0f6760a4803427d7])[..]);
+
+        // first test vector of plonky2's `poseidon_goldilocks.rs`
+        let mut state = [Fr::zero(); PLONKY2_WIDTH];
+        permute(&mut state);
+        assert_eq!(state[..], elements(&[
+            0x3c18a9786cb0b359, 0xc4055e3364a246c3, 0x7953db0ab48808f4, 0xc71603f33a1144ca,
+            0xd7709673896996dc, 0x46a84e87642f44ed, 0xd032648251ee0b3c, 0x1c687363b207df62,
+            0xdf8565563e8045fe, 0x40f5b37ff4254dae, 0xd070f637b431067c, 0x1792b1c4342109d7,
+        ])[..]);
+    }
+
+    #[test]
+    fn test_plonky2_hashing() {
+        assert_eq!(hash_n_to_hash_no_pad(&[]), [Fr::zero(); NUM_HASH_OUT_ELTS]);
+
+        let inputs = elements(&(1..=10).collect::<Vec<u64>>());
+        let mut state = [Fr::zero(); PLONKY2_WIDTH];
+        state[..8].copy_from_slice(&inputs[..8]);
+        permute(&mut state);
+        // overwrite mode, the rest of the rate keeps the permuted values
+        state[..2].copy_from_slice(&inputs[8..]);
+        permute(&mut state);
+        assert_eq!(hash_n_to_hash_no_pad(&inputs)[..], state[..4]);
+
+        let left = hash_n_to_hash_no_pad(&inputs[..3]);
+        let right = hash_n_to_hash_no_pad(&inputs[3..]);
+        let mut concatenated = left.to_vec();
+        concatenated.extend_from_slice(&right);
+        // a single chunk, so compression is the same as hashing the concatenation
+        assert_eq!(two_to_one(left, right), hash_n_to_hash_no_pad(&concatenated));
+    }
+}
@@ -0,0 +1,154 @@
+use crate::pairing::ff::PrimeField;
+use super::{biguint_to_repr, repr_to_biguint};
+
+/// Grain LFSR from the reference implementation of the Poseidon paper
+/// (`generate_parameters_grain.sage`) for the prime field and `x^alpha` sbox
+pub struct Grain {
+    state: [bool; 80],
+}
+
+impl Grain {
+    pub fn new(field_size: u32, t: u32, full_rounds: u32, partial_rounds: u32) -> Self {
+        Self::new_with_sbox_bits(0, field_size, t, full_rounds, partial_rounds)
+    }
+
+    /// Same as `new`, but with custom bits that describe the sbox. The reference
+    /// uses 0 for `x^alpha`, but some implementations use 1 for it, e.g. neptune
+    pub fn new_with_sbox_bits(sbox: u32, field_size: u32, t: u32, full_rounds: u32, partial_rounds: u32) -> Self {
+        let mut bits = Vec::with_capacity(80);
+        let mut append = |value: u32, len: usize| {
+            for i in (0..len).rev() {
+                bits.push((value >> i) & 1 == 1);
+            }
+        };
+        // prime field
+        append(1, 2);
+        append(sbox, 4);
+        append(field_size, 12);
+        append(t, 12);
+        append(full_rounds, 10);
+        append(partial_rounds, 10);
+        append((1 << 30) - 1, 30);
+
+        let mut state = [false; 80];
+        state.copy_from_slice(&bits);
+        let mut grain = Self { state };
+        for _ in 0..160 {
+            grain.update();
+        }
+
+        grain
+    }
+
+    fn update(&mut self) -> bool {
+        let s = &self.state;
+        let new_bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.copy_within(1.., 0);
+        self.state[79] = new_bit;
+
+        new_bit
+    }
+
+    /// Output bits are filtered by pairs: the second bit is taken if the first one is set
+    pub fn next_bit(&mut self) -> bool {
+        loop {
+            let take = self.update();
+            let bit = self.update();
+            if take {
+                return bit;
+            }
+        }
+    }
+
+    /// Big endian number of `F::NUM_BITS` bits, values that are not in the field are skipped
+    pub fn next_field_element<F: PrimeField>(&mut self) -> F {
+        let modulus = repr_to_biguint::<F>(&F::char());
+        loop {
+            let mut value = num_bigint::BigUint::from(0u64);
+            for _ in 0..F::NUM_BITS {
+                value <<= 1;
+                if self.next_bit() {
+                    value += 1u64;
+                }
+            }
+            if value < modulus {
+                return F::from_repr(biguint_to_repr::<F>(&value)).unwrap();
+            }
+        }
+    }
+
+    /// Same as `next_field_element`, but the value is reduced instead of
+    /// being rejected, as done for MDS matrix sampling in some implementations
+    pub fn next_field_element_without_rejection<F: PrimeField>(&mut self) -> F {
+        let modulus = repr_to_biguint::<F>(&F::char());
+        let mut value = num_bigint::BigUint::from(0u64);
+        for _ in 0..F::NUM_BITS {
+            value <<= 1;
+            if self.next_bit() {
+                value += 1u64;
+            }
+        }
+        let value = value % modulus;
+
+        F::from_repr(biguint_to_repr::<F>(&value)).unwrap()
+    }
+}
+
+/// Round constants for `full_rounds + partial_rounds` rounds of width `t`
+pub fn grain_round_constants<F: PrimeField>(t: u32, full_rounds: u32, partial_rounds: u32) -> (Grain, Vec<F>) {
+    grain_round_constants_with_sbox_bits(0, t, full_rounds, partial_rounds)
+}
+
+/// Same as `grain_round_constants`, but with custom sbox bits, see `Grain::new_with_sbox_bits`
+pub fn grain_round_constants_with_sbox_bits<F: PrimeField>(sbox: u32, t: u32, full_rounds: u32, partial_rounds: u32) -> (Grain, Vec<F>) {
+    let mut grain = Grain::new_with_sbox_bits(sbox, F::NUM_BITS, t, full_rounds, partial_rounds);
+    let constants = (0..((full_rounds + partial_rounds) * t)).map(|_| grain.next_field_element()).collect();
+
+    (grain, constants)
+}
+
+/// Cauchy MDS matrix `M[i][j] = 1 / (x_i + y_j)` from `2t` distinct reduced
+/// elements drawn after the round constants, skipping `select` candidate
+/// matrices the same way as halo2's `generate_mds` does
+pub fn grain_mds_matrix<F: PrimeField>(grain: &mut Grain, t: u32, mut select: usize) -> Vec<F> {
+    let t = t as usize;
+    loop {
+        let elements: Vec<F> = (0..(2 * t)).map(|_| grain.next_field_element_without_rejection()).collect();
+        let unique = elements.iter().enumerate().all(|(i, e)| !elements[..i].contains(e));
+        if !unique {
+            continue;
+        }
+        if select != 0 {
+            select -= 1;
+            continue;
+        }
+
+        let (xs, ys) = elements.split_at(t);
+        let mut matrix = Vec::with_capacity(t * t);
+        for x in xs.iter() {
+            for y in ys.iter() {
+                let mut sum = *x;
+                sum.add_assign(y);
+                matrix.push(sum.inverse().expect("x_i + y_j must not be zero"));
+            }
+        }
+
+        return matrix;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::bn256::Fr;
+    use super::*;
+
+    #[test]
+    fn test_grain_matches_reference_constants() {
+        // first constants of the reference BN254 parameters for t = 3,
+        // R_F = 8, R_P = 57 as used by circomlib
+        let (_, constants) = grain_round_constants::<Fr>(3, 8, 57);
+        assert_eq!(format!("{}", constants[0].into_repr()), "0x0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e");
+        assert_eq!(format!("{}", constants[1].into_repr()), "0x00f1445235f2148c5986587169fc1bcd887b08d4d00868df5696fff40956e864");
+        assert_eq!(format!("{}", constants[2].into_repr()), "0x08dff3487e8ac99e1f29a058d0fa80b930c728730b7ab36ce879f3890ecf73f5");
+    }
+}
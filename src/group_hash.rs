@@ -1,21 +1,51 @@
 use crate::tiny_keccak::Keccak;
 use crate::blake2_rfc::blake2s::Blake2s;
+use crate::blake2_rfc::blake2b::Blake2b;
+use sha2::{Digest, Sha256};
+
+/// Length of the personalization tag. Shorter tags are padded with zeroes.
+/// Hashers from the Blake2 family place the tag into the parameter block (Blake2b
+/// pads it further to 16 bytes), other hashers prepend the padded tag to the input,
+/// so the tag is always unambiguously separated from the data.
+pub const PERSONALIZATION_LEN: usize = 8;
+
+/// Hash with 32 bytes of output. Finalization resets the hasher to the
+/// initial state for the same personalization.
 pub trait GroupHasher {
     fn new(personalization: &[u8]) -> Self;
     fn update(&mut self, data: &[u8]);
     fn finalize(&mut self) -> Vec<u8>;
+    /// Name that is recorded in generated parameters
+    fn name() -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+fn padded_personalization(personalization: &[u8]) -> [u8; PERSONALIZATION_LEN] {
+    assert!(personalization.len() <= PERSONALIZATION_LEN, "personalization is too long");
+    let mut padded = [0u8; PERSONALIZATION_LEN];
+    padded[..personalization.len()].copy_from_slice(personalization);
+
+    padded
 }
 
 pub struct BlakeHasher {
-    h: Blake2s
+    h: Blake2s,
+    personalization: [u8; PERSONALIZATION_LEN],
 }
 
 impl GroupHasher for BlakeHasher {
+    fn name() -> &'static str {
+        "blake2s"
+    }
+
     fn new(personalization: &[u8]) -> Self {
-        let h = Blake2s::with_params(32, &[], &[], personalization);
+        let personalization = padded_personalization(personalization);
+        let h = Blake2s::with_params(32, &[], &[], &personalization);
 
         Self {
-            h: h
+            h,
+            personalization
         }
     }
 
@@ -24,26 +54,68 @@ impl GroupHasher for BlakeHasher {
     }
 
     fn finalize(&mut self) -> Vec<u8> {
-        let new_h = Blake2s::with_params(32, &[], &[], &[]);
+        let new_h = Blake2s::with_params(32, &[], &[], &self.personalization);
         let h = std::mem::replace(&mut self.h, new_h);
 
         let result = h.finalize();
 
-        result.as_ref().to_vec().clone()
+        result.as_ref().to_vec()
+    }
+}
+
+/// Blake2b truncated to 32 bytes of output
+pub struct Blake2bHasher {
+    h: Blake2b,
+    personalization: [u8; PERSONALIZATION_LEN],
+}
+
+impl GroupHasher for Blake2bHasher {
+    fn name() -> &'static str {
+        "blake2b"
+    }
+
+    fn new(personalization: &[u8]) -> Self {
+        let personalization = padded_personalization(personalization);
+        let h = Blake2b::with_params(32, &[], &[], &personalization);
+
+        Self {
+            h,
+            personalization
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.h.update(data);
+    }
+
+    fn finalize(&mut self) -> Vec<u8> {
+        let new_h = Blake2b::with_params(32, &[], &[], &self.personalization);
+        let h = std::mem::replace(&mut self.h, new_h);
+
+        let result = h.finalize();
+
+        result.as_ref().to_vec()
     }
 }
 
 pub struct Keccak256Hasher {
-    h: Keccak
+    h: Keccak,
+    personalization: [u8; PERSONALIZATION_LEN],
 }
 
 impl GroupHasher for Keccak256Hasher {
+    fn name() -> &'static str {
+        "keccak256"
+    }
+
     fn new(personalization: &[u8]) -> Self {
+        let personalization = padded_personalization(personalization);
         let mut h = Keccak::new_keccak256();
-        h.update(personalization);
+        h.update(&personalization);
 
         Self {
-            h: h
+            h,
+            personalization
         }
     }
 
@@ -52,7 +124,8 @@ impl GroupHasher for Keccak256Hasher {
     }
 
     fn finalize(&mut self) -> Vec<u8> {
-        let new_h = Keccak::new_keccak256();
+        let mut new_h = Keccak::new_keccak256();
+        new_h.update(&self.personalization);
         let h = std::mem::replace(&mut self.h, new_h);
 
         let mut res: [u8; 32] = [0; 32];
@@ -60,4 +133,84 @@ impl GroupHasher for Keccak256Hasher {
 
         res[..].to_vec()
     }
-}
\ No newline at end of file
+}
+
+pub struct Sha256Hasher {
+    h: Sha256,
+    personalization: [u8; PERSONALIZATION_LEN],
+}
+
+impl GroupHasher for Sha256Hasher {
+    fn name() -> &'static str {
+        "sha256"
+    }
+
+    fn new(personalization: &[u8]) -> Self {
+        let personalization = padded_personalization(personalization);
+        let mut h = Sha256::new();
+        h.input(personalization);
+
+        Self {
+            h,
+            personalization
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.h.input(data);
+    }
+
+    fn finalize(&mut self) -> Vec<u8> {
+        let mut new_h = Sha256::new();
+        new_h.input(self.personalization);
+        let h = std::mem::replace(&mut self.h, new_h);
+
+        h.result().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn digest<H: GroupHasher>(personalization: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut h = H::new(personalization);
+        h.update(data);
+        let first = h.finalize();
+
+        // finalization resets to the personalized state
+        h.update(data);
+        assert_eq!(h.finalize(), first);
+
+        first
+    }
+
+    fn check_personalization<H: GroupHasher>() {
+        // short tags are padded
+        assert_eq!(digest::<H>(b"Tag", b"data"), digest::<H>(b"Tag\0\0\0\0\0", b"data"));
+        assert!(digest::<H>(b"Tag", b"data") != digest::<H>(b"Ta", b"gdata"));
+        assert!(digest::<H>(b"Tag", b"data") != digest::<H>(b"", b"data"));
+        assert_eq!(digest::<H>(b"Tag", b"data").len(), 32);
+    }
+
+    #[test]
+    fn test_personalization_semantics() {
+        check_personalization::<BlakeHasher>();
+        check_personalization::<Blake2bHasher>();
+        check_personalization::<Keccak256Hasher>();
+        check_personalization::<Sha256Hasher>();
+    }
+
+    #[test]
+    fn test_prefix_hashers_prepend_padded_tag() {
+        let mut h = Sha256::new();
+        h.input(b"Tag\0\0\0\0\0data");
+        assert_eq!(digest::<Sha256Hasher>(b"Tag", b"data"), h.result().to_vec());
+
+        let mut h = Keccak::new_keccak256();
+        h.update(b"Tag\0\0\0\0\0data");
+        let mut expected = [0u8; 32];
+        h.finalize(&mut expected);
+        assert_eq!(digest::<Keccak256Hasher>(b"Tag", b"data"), expected.to_vec());
+    }
+}
@@ -0,0 +1,238 @@
+use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
+use num_traits::One;
+
+use crate::pairing::ff::{Field, PrimeField};
+use crate::pairing::ff::ScalarEngine;
+use super::{PoseidonEngine, PoseidonHashParams, PowerSBox, RoundConstantsPosition, SBox, POWER_SBOX_WINDOW_SIZE, repr_to_biguint, biguint_to_repr};
+
+/// Makes `x^(1/e)` for an sbox `x^e`, or returns `None` if the sbox doesn't
+/// report the exponent or `x^e` is not a permutation of the field
+pub fn inverse_sbox<E: ScalarEngine, S: SBox<E>>(sbox: &S) -> Option<PowerSBox<E>> {
+    let exponent = repr_to_biguint::<E::Fr>(&sbox.exponent()?);
+    let group_order = repr_to_biguint::<E::Fr>(&E::Fr::char()) - BigUint::one();
+
+    // e * d = 1 mod (p - 1)
+    let e = BigInt::from_biguint(Sign::Plus, exponent.clone());
+    let n = BigInt::from_biguint(Sign::Plus, group_order.clone());
+    let gcd = e.extended_gcd(&n);
+    if !gcd.gcd.is_one() {
+        return None;
+    }
+    let power = gcd.x.mod_floor(&n).to_biguint().unwrap();
+
+    // digits for windowed exponentiation, most significant first,
+    // there should be at least two of them
+    let window = BigUint::from(1u64 << POWER_SBOX_WINDOW_SIZE);
+    let mut precomputed_indexes = vec![];
+    let mut rest = power.clone();
+    while precomputed_indexes.len() < 2 || rest != BigUint::from(0u64) {
+        let (quotient, digit) = rest.div_rem(&window);
+        let digit = digit.to_bytes_le()[0] as usize;
+        precomputed_indexes.push(digit);
+        rest = quotient;
+    }
+    precomputed_indexes.reverse();
+
+    let inv = if exponent.bits() <= 64 {
+        biguint_to_repr::<E::Fr>(&exponent).as_ref()[0]
+    } else {
+        0
+    };
+
+    Some(PowerSBox {
+        power: biguint_to_repr::<E::Fr>(&power),
+        precomputed_indexes,
+        inv,
+    })
+}
+
+/// Inverts a `t x t` matrix given by rows with Gauss-Jordan elimination
+pub fn invert_matrix<E: ScalarEngine>(matrix: &[E::Fr], t: usize) -> Option<Vec<E::Fr>> {
+    assert_eq!(matrix.len(), t * t);
+    let mut m = matrix.to_vec();
+    let mut inv = vec![E::Fr::zero(); t * t];
+    for i in 0..t {
+        inv[i * t + i] = E::Fr::one();
+    }
+
+    for col in 0..t {
+        let pivot = (col..t).find(|&row| !m[row * t + col].is_zero())?;
+        if pivot != col {
+            for j in 0..t {
+                m.swap(pivot * t + j, col * t + j);
+                inv.swap(pivot * t + j, col * t + j);
+            }
+        }
+
+        let pivot_inv = m[col * t + col].inverse().unwrap();
+        for j in 0..t {
+            m[col * t + j].mul_assign(&pivot_inv);
+            inv[col * t + j].mul_assign(&pivot_inv);
+        }
+
+        for row in 0..t {
+            if row == col || m[row * t + col].is_zero() {
+                continue;
+            }
+            let factor = m[row * t + col];
+            for j in 0..t {
+                let mut tmp = m[col * t + j];
+                tmp.mul_assign(&factor);
+                m[row * t + j].sub_assign(&tmp);
+
+                let mut tmp = inv[col * t + j];
+                tmp.mul_assign(&factor);
+                inv[row * t + j].sub_assign(&tmp);
+            }
+        }
+    }
+
+    Some(inv)
+}
+
+/// Precomputed data to run the permutation backwards
+pub struct PoseidonInverse<E: PoseidonEngine> {
+    t: usize,
+    inverse_mds: Vec<E::Fr>,
+    inverse_sbox: PowerSBox<E>,
+}
+
+impl<E: PoseidonEngine> Clone for PoseidonInverse<E> {
+    fn clone(&self) -> Self {
+        Self {
+            t: self.t,
+            inverse_mds: self.inverse_mds.clone(),
+            inverse_sbox: self.inverse_sbox.clone()
+        }
+    }
+}
+
+impl<E: PoseidonEngine> PoseidonInverse<E> {
+    pub fn new(
+        params: &E::Params
+    ) -> Self {
+        let t = params.state_width() as usize;
+        let mut mds = Vec::with_capacity(t * t);
+        for row in 0..t {
+            mds.extend_from_slice(params.mds_matrix_row(row as u32));
+        }
+
+        Self {
+            t,
+            inverse_mds: invert_matrix::<E>(&mds, t).expect("MDS matrix must be invertible"),
+            inverse_sbox: inverse_sbox::<E, _>(params.sbox()).expect("sbox must be an invertible power map"),
+        }
+    }
+
+    pub fn inverse_mds_matrix_row(&self, row: u32) -> &[E::Fr] {
+        let start = self.t * (row as usize);
+
+        &self.inverse_mds[start..(start + self.t)]
+    }
+
+    pub fn apply(
+        &self,
+        params: &E::Params,
+        state: &mut [E::Fr]
+    ) {
+        assert_eq!(state.len(), params.state_width() as usize);
+        debug_assert!(params.num_full_rounds() % 2 == 0 || params.num_partial_rounds() == 0);
+        let constants_first = params.round_constants_position() == RoundConstantsPosition::BeforeSBox;
+        let half_of_full_rounds = params.num_full_rounds() / 2;
+        let partial_rounds_end = half_of_full_rounds + params.num_partial_rounds();
+        let num_rounds = params.num_full_rounds() + params.num_partial_rounds();
+        let sbox_idx = params.partial_round_sbox_index() as usize;
+        let mut scratch = vec![E::Fr::zero(); state.len()];
+
+        for round in (0..num_rounds).rev() {
+            if !constants_first {
+                for (s, c) in state.iter_mut().zip(params.round_constants(round).iter()) {
+                    s.sub_assign(c);
+                }
+            }
+
+            for (row, place_into) in scratch.iter_mut().enumerate() {
+                *place_into = super::scalar_product::<E>(state, self.inverse_mds_matrix_row(row as u32));
+            }
+            state.copy_from_slice(&scratch);
+
+            if round < half_of_full_rounds || round >= partial_rounds_end {
+                self.inverse_sbox.apply(state);
+            } else {
+                self.inverse_sbox.apply(&mut state[sbox_idx..(sbox_idx + 1)]);
+            }
+
+            if constants_first {
+                for (s, c) in state.iter_mut().zip(params.round_constants(round).iter()) {
+                    s.sub_assign(c);
+                }
+            }
+        }
+    }
+}
+
+/// Runs `poseidon_mimc` backwards, so `poseidon_mimc_inverse(poseidon_mimc(x)) == x`
+pub fn poseidon_mimc_inverse<E: PoseidonEngine>(
+    params: &E::Params,
+    state: &[E::Fr]
+) -> Vec<E::Fr> {
+    let mut state = state.to_vec();
+    PoseidonInverse::<E>::new(params).apply(params, &mut state);
+
+    state
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use std::marker::PhantomData;
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::{poseidon_mimc, CubicSBox, InversionSBox, QuinticSBox};
+    use super::*;
+
+    #[test]
+    fn test_bn256_inverse_sboxes() {
+        let rng = &mut thread_rng();
+        let values: Vec<Fr> = (0..10).map(|_| rng.gen()).collect();
+
+        let quintic = QuinticSBox::<Bn256> { _marker: PhantomData };
+        let inverse = inverse_sbox::<Bn256, _>(&quintic).unwrap();
+        let mut state = values.clone();
+        quintic.apply(&mut state);
+        inverse.apply(&mut state);
+        assert_eq!(state, values);
+        assert_eq!(inverse.inv, 5);
+
+        let inversion = InversionSBox::<Bn256> { _marker: PhantomData };
+        let inverse = inverse_sbox::<Bn256, _>(&inversion).unwrap();
+        let mut state = values.clone();
+        inversion.apply(&mut state);
+        inverse.apply(&mut state);
+        assert_eq!(state, values);
+
+        // x^3 is not a permutation of the bn256 scalar field
+        assert!(inverse_sbox::<Bn256, _>(&CubicSBox::<Bn256> { _marker: PhantomData }).is_none());
+    }
+
+    #[test]
+    fn test_bn256_inverse_permutation() {
+        let rng = &mut thread_rng();
+        for params in [
+            Bn256PoseidonParams::new_checked_2_into_1(),
+            Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 4, 60, 8, 128)
+        ].iter() {
+            let inverse = PoseidonInverse::<Bn256>::new(params);
+            let state: Vec<Fr> = (0..params.state_width()).map(|_| rng.gen()).collect();
+
+            let permuted = poseidon_mimc::<Bn256>(params, &state);
+            assert_eq!(poseidon_mimc_inverse::<Bn256>(params, &permuted), state);
+
+            let mut inverted = state.clone();
+            inverse.apply(params, &mut inverted);
+            assert_eq!(poseidon_mimc::<Bn256>(params, &inverted), state);
+        }
+    }
+}
@@ -6,30 +6,65 @@ extern crate byteorder;
 extern crate alga;
 extern crate approx;
 extern crate mathru;
-
-use crate::pairing::ff::{Field, PrimeField};
-use crate::pairing::{Engine};
+#[cfg(feature = "digest")]
+extern crate digest_crate;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "arkworks")]
+extern crate ark_crypto_primitives;
+#[cfg(feature = "arkworks")]
+extern crate ark_ff;
+
+use crate::pairing::ff::{Field, PrimeField, PrimeFieldRepr};
+use crate::pairing::ff::ScalarEngine;
+// `PrimeField` derives refer to `crate::ff`
+use crate::pairing::ff;
 use std::marker::PhantomData;
 
 use crate::rand::{Rng};
 
+pub use self::error::ParamsError;
+
 pub mod group_hash;
+pub mod error;
 mod constants;
 mod algebra;
 pub mod specialization;
+pub mod bytes;
+pub mod xof;
+pub mod batch;
+pub mod inverse;
+pub mod trace;
+pub mod air;
+pub mod plonk;
+pub mod cost;
+pub mod sampling;
+pub mod grain;
+#[cfg(feature = "digest")]
+pub mod digest;
+#[cfg(feature = "arkworks")]
+pub mod arkworks;
 
 pub mod bn256;
+pub mod bls12_381;
+pub mod pasta;
+pub mod stark252;
+pub mod goldilocks;
 
-pub trait SBox<E: Engine>: Sized + Clone {
+pub trait SBox<E: ScalarEngine>: Sized + Clone {
     fn apply(&self, elements: &mut [E::Fr]);
+    /// Exponent `e` if the sbox is `x^e`, used to derive the inverse permutation
+    fn exponent(&self) -> Option<<E::Fr as PrimeField>::Repr> {
+        None
+    }
 }
 
 #[derive(Clone)]
-pub struct CubicSBox<E: Engine> {
+pub struct CubicSBox<E: ScalarEngine> {
     pub _marker: PhantomData<E>
 }
 
-impl<E: Engine>SBox<E> for CubicSBox<E> {
+impl<E: ScalarEngine>SBox<E> for CubicSBox<E> {
     fn apply(&self, elements: &mut [E::Fr]) {
         for element in elements.iter_mut() {
             let mut squared = *element;
@@ -37,14 +72,18 @@ impl<E: Engine>SBox<E> for CubicSBox<E> {
             element.mul_assign(&squared);
         }
     }
+
+    fn exponent(&self) -> Option<<E::Fr as PrimeField>::Repr> {
+        Some(<E::Fr as PrimeField>::Repr::from(3))
+    }
 }
 
 #[derive(Clone)]
-pub struct QuinticSBox<E: Engine> {
+pub struct QuinticSBox<E: ScalarEngine> {
     pub _marker: PhantomData<E>
 }
 
-impl<E: Engine>SBox<E> for QuinticSBox<E> {
+impl<E: ScalarEngine>SBox<E> for QuinticSBox<E> {
     fn apply(&self, elements: &mut [E::Fr]) {
         for element in elements.iter_mut() {
             let mut quad = *element;
@@ -53,20 +92,46 @@ impl<E: Engine>SBox<E> for QuinticSBox<E> {
             element.mul_assign(&quad);
         }
     }
+
+    fn exponent(&self) -> Option<<E::Fr as PrimeField>::Repr> {
+        Some(<E::Fr as PrimeField>::Repr::from(5))
+    }
+}
+
+#[derive(Clone)]
+pub struct SepticSBox<E: ScalarEngine> {
+    pub _marker: PhantomData<E>
+}
+
+impl<E: ScalarEngine>SBox<E> for SepticSBox<E> {
+    fn apply(&self, elements: &mut [E::Fr]) {
+        for element in elements.iter_mut() {
+            let mut squared = *element;
+            squared.square();
+            let mut sixth = squared;
+            sixth.square();
+            sixth.mul_assign(&squared);
+            element.mul_assign(&sixth);
+        }
+    }
+
+    fn exponent(&self) -> Option<<E::Fr as PrimeField>::Repr> {
+        Some(<E::Fr as PrimeField>::Repr::from(7))
+    }
 }
 
 const POWER_SBOX_WINDOW_SIZE: usize = 4;
 
 #[derive(Clone)]
-pub struct PowerSBox<E: Engine> {
+pub struct PowerSBox<E: ScalarEngine> {
     pub power: <E::Fr as PrimeField>::Repr,
     pub precomputed_indexes: Vec<usize>,
     pub inv: u64,
 }
 
-impl<E: Engine>SBox<E> for PowerSBox<E> {
+impl<E: ScalarEngine>SBox<E> for PowerSBox<E> {
     fn apply(&self, elements: &mut [E::Fr]) {
-        if self.precomputed_indexes.len() != 0 {
+        if !self.precomputed_indexes.is_empty() {
             let mut table = [E::Fr::zero(); 1 << POWER_SBOX_WINDOW_SIZE];
             table[0] = E::Fr::one();
 
@@ -74,9 +139,9 @@ impl<E: Engine>SBox<E> for PowerSBox<E> {
                 let mut current = *element;
                 table[1] = current;
                 
-                for i in 2..(1 << POWER_SBOX_WINDOW_SIZE) {
+                for entry in table.iter_mut().skip(2) {
                     current.mul_assign(&*element);
-                    table[i] = current;
+                    *entry = current;
                 }
 
                 let bound = self.precomputed_indexes.len() - 1;
@@ -98,18 +163,22 @@ impl<E: Engine>SBox<E> for PowerSBox<E> {
             }
         } else {
             for element in elements.iter_mut() {
-                *element = element.pow(&self.power);
+                *element = element.pow(self.power);
             }
         }
     }
+
+    fn exponent(&self) -> Option<<E::Fr as PrimeField>::Repr> {
+        Some(self.power)
+    }
 }
 
 #[derive(Clone)]
-pub struct InversionSBox<E: Engine> {
+pub struct InversionSBox<E: ScalarEngine> {
     pub _marker: PhantomData<E>
 }
 
-fn batch_inversion<E: Engine>(v: &mut [E::Fr]) {
+fn batch_inversion<E: ScalarEngine>(v: &mut [E::Fr]) {
     // Montgomery’s Trick and Fast Implementation of Masked AES
     // Genelle, Prouff and Quisquater
     // Section 3.2
@@ -121,7 +190,7 @@ fn batch_inversion<E: Engine>(v: &mut [E::Fr]) {
         // Ignore zero elements
         .filter(|g| !g.is_zero())
     {
-        tmp.mul_assign(&g);
+        tmp.mul_assign(g);
         prod.push(tmp);
     }
 
@@ -139,20 +208,38 @@ fn batch_inversion<E: Engine>(v: &mut [E::Fr]) {
     {
         // tmp := tmp * g.z; g.z := tmp * s = 1/z
         let mut newtmp = tmp;
-        newtmp.mul_assign(&g);
+        newtmp.mul_assign(g);
         *g = tmp;
         g.mul_assign(&s);
         tmp = newtmp;
     }
 }
 
-impl<E: Engine>SBox<E> for InversionSBox<E> {
+impl<E: ScalarEngine>SBox<E> for InversionSBox<E> {
     fn apply(&self, elements: &mut [E::Fr]) {
         batch_inversion::<E>(elements);
     }
+
+    // zero is mapped to zero, so this is just x^(p-2)
+    fn exponent(&self) -> Option<<E::Fr as PrimeField>::Repr> {
+        let mut power = E::Fr::char();
+        power.sub_noborrow(&<E::Fr as PrimeField>::Repr::from(2));
+
+        Some(power)
+    }
 }
 
-pub trait PoseidonHashParams<E: Engine>: PoseidonParamsInternal<E> {
+/// Where round constants are added within a round
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RoundConstantsPosition {
+    /// `ark -> sbox -> mds`, as in the Poseidon paper
+    #[default]
+    BeforeSBox,
+    /// `sbox -> mds -> ark`, as in Mina
+    AfterMds,
+}
+
+pub trait PoseidonHashParams<E: ScalarEngine>: PoseidonParamsInternal<E> {
     type SBox: SBox<E>;
     fn capacity(&self) -> u32;
     fn rate(&self) -> u32;
@@ -170,18 +257,25 @@ pub trait PoseidonHashParams<E: Engine>: PoseidonParamsInternal<E> {
     fn absorbtion_cycle_len(&self) -> u32 {
         self.rate()
     }
+    /// Index of the state element that goes through the sbox in partial rounds
+    fn partial_round_sbox_index(&self) -> u32 {
+        self.state_width() - 1
+    }
     fn compression_rate(&self) -> u32 {
         self.absorbtion_cycle_len() / self.output_len()
     }
+    fn round_constants_position(&self) -> RoundConstantsPosition {
+        RoundConstantsPosition::BeforeSBox
+    }
 
     fn sbox(&self) -> &Self::SBox;
 }
 
-pub trait PoseidonParamsInternal<E: Engine>: Send + Sync + Sized + Clone {
+pub trait PoseidonParamsInternal<E: ScalarEngine>: Send + Sync + Sized + Clone {
     fn set_round_constants(&mut self, to: Vec<E::Fr>);
 }
 
-pub trait PoseidonEngine: Engine {
+pub trait PoseidonEngine: ScalarEngine {
     type Params: PoseidonHashParams<Self>; 
 }
 
@@ -192,13 +286,80 @@ pub fn poseidon_hash<E: PoseidonEngine>(
     sponge::<E>(params, input)
 }
 
+/// Checks the invariants every other function relies on
+pub fn check_params<E: ScalarEngine, P: PoseidonHashParams<E>>(params: &P) -> Result<(), ParamsError> {
+    check_shape(
+        params.absorbtion_cycle_len(),
+        params.capacity(),
+        params.num_full_rounds(),
+        params.num_partial_rounds(),
+        params.output_len()
+    )
+}
+
+// shared by `check_params` and the params builders, which validate the shape
+// before generating any constants
+pub(crate) fn check_shape(rate: u32, capacity: u32, full_rounds: u32, partial_rounds: u32, output_len: u32) -> Result<(), ParamsError> {
+    if rate == 0 {
+        return Err(ParamsError::ZeroRate);
+    }
+    if capacity == 0 {
+        return Err(ParamsError::ZeroCapacity);
+    }
+    // full rounds are split around partial ones, without them any number works
+    if full_rounds % 2 == 1 && partial_rounds != 0 {
+        return Err(ParamsError::OddNumberOfFullRounds(full_rounds));
+    }
+    if full_rounds + partial_rounds == 0 {
+        return Err(ParamsError::NoRounds);
+    }
+    if rate < output_len {
+        return Err(ParamsError::RateBelowOutputLength { rate, output_len });
+    }
+
+    Ok(())
+}
+
+// generic attacks on the capacity bound the security level by half of its bits
+pub(crate) fn check_security_level<F: PrimeField>(capacity: u32, security_level: u32) -> Result<(), ParamsError> {
+    let max = capacity.saturating_mul(F::CAPACITY) / 2;
+    if security_level == 0 || security_level > max {
+        return Err(ParamsError::InvalidSecurityLevel { security_level, max });
+    }
+
+    Ok(())
+}
+
+/// Same as `poseidon_hash`, but returns an error for invalid params
+pub fn try_poseidon_hash<E: PoseidonEngine>(
+    params: &E::Params,
+    input: &[E::Fr]
+) -> Result<Vec<E::Fr>, ParamsError> {
+    check_params(params)?;
+
+    Ok(sponge::<E>(params, input))
+}
+
+/// Same as `poseidon_mimc`, but returns an error for invalid params or state length
+pub fn try_poseidon_mimc<E: PoseidonEngine>(
+    params: &E::Params,
+    old_state: &[E::Fr]
+) -> Result<Vec<E::Fr>, ParamsError> {
+    check_params(params)?;
+    if old_state.len() != params.state_width() as usize {
+        return Err(ParamsError::InvalidStateLength { expected: params.state_width() as usize, actual: old_state.len() });
+    }
+
+    Ok(poseidon_mimc::<E>(params, old_state))
+}
+
 fn sponge<E: PoseidonEngine>(
     params: &E::Params,
     input: &[E::Fr]
 ) -> Vec<E::Fr> {
 
     let mut stateful = StatefulSponge::<E>::new(params);
-    stateful.absorb(&input);
+    stateful.absorb(input);
 
     let mut output = Vec::with_capacity(params.capacity() as usize);
     for _ in 0..params.capacity() {
@@ -213,88 +374,141 @@ pub fn poseidon_mimc<E: PoseidonEngine>(
     old_state: &[E::Fr]
 ) -> Vec<E::Fr> {
     let mut state = old_state.to_vec();
-    debug_assert!(params.num_full_rounds() % 2 == 0);
-    let half_of_full_rounds = params.num_full_rounds() / 2;
     let mut mds_application_scratch = vec![E::Fr::zero(); state.len()];
-    assert_eq!(state.len(), params.state_width() as usize);
+    poseidon_mimc_in_place::<E>(params, &mut state, &mut mds_application_scratch);
+
+    state
+}
+
+/// Same as `poseidon_mimc`, but permutes the state in place using
+/// caller provided scratch space of the same length
+pub fn poseidon_mimc_in_place<E: PoseidonEngine>(
+    params: &E::Params,
+    state: &mut [E::Fr],
+    mds_application_scratch: &mut [E::Fr]
+) {
+    poseidon_mimc_in_parts::<E>(params, (state, &mut []), (mds_application_scratch, &mut []));
+}
+
+/// Permutes a fixed width state without touching the heap
+pub fn permute<E: PoseidonEngine, const T: usize>(
+    params: &E::Params,
+    state: &mut [E::Fr; T]
+) {
+    let mut mds_application_scratch = [E::Fr::zero(); T];
+    poseidon_mimc_in_place::<E>(params, &mut state[..], &mut mds_application_scratch[..]);
+}
 
-    let last_elem_idx = state.len() - 1;
+/// Permutation of a state that is stored as two consecutive parts, e.g. as
+/// separate rate and capacity arrays. Scratch space must be split the same way.
+pub fn poseidon_mimc_in_parts<E: PoseidonEngine>(
+    params: &E::Params,
+    state: (&mut [E::Fr], &mut [E::Fr]),
+    mds_application_scratch: (&mut [E::Fr], &mut [E::Fr])
+) {
+    let (head, tail) = state;
+    let (head_scratch, tail_scratch) = mds_application_scratch;
+    debug_assert!(params.num_full_rounds() % 2 == 0 || params.num_partial_rounds() == 0);
+    let half_of_full_rounds = params.num_full_rounds() / 2;
+    assert_eq!(head.len() + tail.len(), params.state_width() as usize);
+    assert_eq!(head_scratch.len(), head.len());
+    assert_eq!(tail_scratch.len(), tail.len());
+    let sbox_idx = params.partial_round_sbox_index() as usize;
+    let constants_first = params.round_constants_position() == RoundConstantsPosition::BeforeSBox;
 
     // full rounds
     for round in 0..half_of_full_rounds {
-        let round_constants = params.round_constants(round);
-    
-        // add round constatnts
-        for (s, c)  in state.iter_mut()
-                    .zip(round_constants.iter()) {
-            s.add_assign(c);
+        if constants_first {
+            add_round_constants::<E>(params.round_constants(round), head, tail);
         }
 
-        params.sbox().apply(&mut state[..]);
+        params.sbox().apply(head);
+        params.sbox().apply(tail);
 
-        // mul state by MDS
-        for (row, place_into) in mds_application_scratch.iter_mut()
-                                        .enumerate() {
-            let tmp = scalar_product::<E>(& state[..], params.mds_matrix_row(row as u32));                           
-            *place_into = tmp;
-        }
+        mul_by_mds::<E>(params, (head, tail), (head_scratch, tail_scratch));
 
-        // place new data into the state
-        state.copy_from_slice(&mds_application_scratch[..]);
+        if !constants_first {
+            add_round_constants::<E>(params.round_constants(round), head, tail);
+        }
     }
 
     // partial rounds
 
     for round in half_of_full_rounds..(params.num_partial_rounds() + half_of_full_rounds){
-        let round_constants = params.round_constants(round);
-    
-        // add round constatnts
-        for (s, c)  in state.iter_mut()
-                    .zip(round_constants.iter()) {
-            s.add_assign(c);
+        if constants_first {
+            add_round_constants::<E>(params.round_constants(round), head, tail);
         }
 
-        params.sbox().apply(&mut state[last_elem_idx..]);
-
-        // mul state by MDS
-        for (row, place_into) in mds_application_scratch.iter_mut()
-                                        .enumerate() {
-            let tmp = scalar_product::<E>(& state[..], params.mds_matrix_row(row as u32));
-            *place_into = tmp;                               
+        // only one element of the state goes through the sbox
+        if sbox_idx < head.len() {
+            params.sbox().apply(&mut head[sbox_idx..(sbox_idx + 1)]);
+        } else {
+            let sbox_idx = sbox_idx - head.len();
+            params.sbox().apply(&mut tail[sbox_idx..(sbox_idx + 1)]);
         }
 
-        // place new data into the state
-        state.copy_from_slice(&mds_application_scratch[..]);
+        mul_by_mds::<E>(params, (head, tail), (head_scratch, tail_scratch));
+
+        if !constants_first {
+            add_round_constants::<E>(params.round_constants(round), head, tail);
+        }
     }
 
     // full rounds
     for round in (params.num_partial_rounds() + half_of_full_rounds)..(params.num_partial_rounds() + params.num_full_rounds()) {
-        let round_constants = params.round_constants(round);
-    
-        // add round constatnts
-        for (s, c)  in state.iter_mut()
-                    .zip(round_constants.iter()) {
-            s.add_assign(c);
+        if constants_first {
+            add_round_constants::<E>(params.round_constants(round), head, tail);
         }
 
-        params.sbox().apply(&mut state[..]);
+        params.sbox().apply(head);
+        params.sbox().apply(tail);
+
+        mul_by_mds::<E>(params, (head, tail), (head_scratch, tail_scratch));
 
-        // mul state by MDS
-        for (row, place_into) in mds_application_scratch.iter_mut()
-                                        .enumerate() {
-            let tmp = scalar_product::<E>(& state[..], params.mds_matrix_row(row as u32));                           
-            *place_into = tmp;
+        if !constants_first {
+            add_round_constants::<E>(params.round_constants(round), head, tail);
         }
+    }
+}
 
-        // place new data into the state
-        state.copy_from_slice(&mds_application_scratch[..]);
+#[inline]
+fn add_round_constants<E: ScalarEngine>(
+    round_constants: &[E::Fr],
+    head: &mut [E::Fr],
+    tail: &mut [E::Fr]
+) {
+    for (s, c) in head.iter_mut().chain(tail.iter_mut())
+                .zip(round_constants.iter()) {
+        s.add_assign(c);
     }
+}
 
-    state
+#[inline]
+fn mul_by_mds<E: PoseidonEngine>(
+    params: &E::Params,
+    state: (&mut [E::Fr], &mut [E::Fr]),
+    mds_application_scratch: (&mut [E::Fr], &mut [E::Fr])
+) {
+    let (head, tail) = state;
+    let (head_scratch, tail_scratch) = mds_application_scratch;
+    let split = head.len();
+
+    for (row, place_into) in head_scratch.iter_mut()
+                                    .chain(tail_scratch.iter_mut())
+                                    .enumerate() {
+        let mds_row = params.mds_matrix_row(row as u32);
+        let mut tmp = scalar_product::<E>(head, &mds_row[..split]);
+        tmp.add_assign(&scalar_product::<E>(tail, &mds_row[split..]));
+        *place_into = tmp;
+    }
+
+    // place new data into the state
+    head.copy_from_slice(head_scratch);
+    tail.copy_from_slice(tail_scratch);
 }
 
 #[inline]
-fn scalar_product<E: Engine> (input: &[E::Fr], by: &[E::Fr]) -> E::Fr {
+fn scalar_product<E: ScalarEngine> (input: &[E::Fr], by: &[E::Fr]) -> E::Fr {
     debug_assert!(input.len() == by.len());
     let mut result = E::Fr::zero();
     for (a, b) in input.iter().zip(by.iter()) {
@@ -306,6 +520,36 @@ fn scalar_product<E: Engine> (input: &[E::Fr], by: &[E::Fr]) -> E::Fr {
     result
 }
 
+// exponent of a power sbox if it fits into a machine word
+pub(crate) fn small_sbox_exponent<E: ScalarEngine, S: SBox<E>>(sbox: &S) -> Option<u64> {
+    let exponent = sbox.exponent()?;
+    let limbs = exponent.as_ref();
+    if limbs[1..].iter().all(|&limb| limb == 0) {
+        Some(limbs[0])
+    } else {
+        None
+    }
+}
+
+pub(crate) fn repr_to_biguint<F: PrimeField>(repr: &F::Repr) -> num_bigint::BigUint {
+    let mut bytes = vec![];
+    repr.write_le(&mut bytes).unwrap();
+
+    num_bigint::BigUint::from_bytes_le(&bytes)
+}
+
+pub(crate) fn biguint_to_repr<F: PrimeField>(value: &num_bigint::BigUint) -> F::Repr {
+    let mut bytes = value.to_bytes_le();
+    let repr_len = F::Repr::default().as_ref().len() * 8;
+    assert!(bytes.len() <= repr_len);
+    bytes.resize(repr_len, 0);
+
+    let mut repr = F::Repr::default();
+    repr.read_le(&bytes[..]).unwrap();
+
+    repr
+}
+
 // For simplicity we'll not generate a matrix using a way from the paper and sampling
 // an element with some zero MSBs and instead just sample and retry
 fn generate_mds_matrix<E: PoseidonEngine, R: Rng>(t: u32, rng: &mut R) -> Vec<E::Fr> {
@@ -352,13 +596,12 @@ fn generate_mds_matrix<E: PoseidonEngine, R: Rng>(t: u32, rng: &mut R) -> Vec<E:
         }
 
         // quick and dirty check for uniqueness of x vs y
-        for i in 0..(t as usize) {
+        for el in x.iter() {
             if invalid {
                 continue;
             }
-            let el = x[i];
             for other in y.iter() {
-                if el == *other {
+                if el == other {
                     invalid = true;
                     break;
                 }
@@ -479,6 +722,15 @@ impl<'a, E: PoseidonEngine> StatefulSponge<'a, E> {
         }
     }
 
+    /// Same as `new`, but returns an error for invalid params
+    pub fn try_new(
+        params: &'a E::Params
+    ) -> Result<Self, ParamsError> {
+        check_params(params)?;
+
+        Ok(Self::new(params))
+    }
+
     pub fn absorb_single_value(
         &mut self,
         value: E::Fr
@@ -492,8 +744,8 @@ impl<'a, E: PoseidonEngine> StatefulSponge<'a, E> {
                 if into.len() < rate {
                     into.push(value);
                 } else {
-                    for i in 0..rate {
-                        self.internal_state[i].add_assign(&into[i]);
+                    for (s, v) in self.internal_state[..rate].iter_mut().zip(into.iter()) {
+                        s.add_assign(v);
                     }
 
                     self.internal_state = poseidon_mimc::<E>(self.params, &self.internal_state);
@@ -547,8 +799,8 @@ impl<'a, E: PoseidonEngine> StatefulSponge<'a, E> {
                 // two cases
                 // either we have accumulated enough already and should to 
                 // a mimc round before accumulating more, or just accumulate more
-                for i in 0..rate {
-                    self.internal_state[i].add_assign(&into[i]);
+                for (s, v) in self.internal_state[..rate].iter_mut().zip(into.iter()) {
+                    s.add_assign(v);
                 }
                 self.internal_state = poseidon_mimc::<E>(self.params, &self.internal_state);
 
@@ -559,10 +811,10 @@ impl<'a, E: PoseidonEngine> StatefulSponge<'a, E> {
                 let op = OpMode::SqueezedInto(sponge_output);
                 self.mode = op;
 
-                return output;
+                output
             },
             OpMode::SqueezedInto(ref mut into) => {
-                if into.len() == 0 {
+                if into.is_empty() {
                     let rate = self.params.rate() as usize;
 
                     self.internal_state = poseidon_mimc::<E>(self.params, &self.internal_state);
@@ -576,10 +828,9 @@ impl<'a, E: PoseidonEngine> StatefulSponge<'a, E> {
                     return output;
                 }
 
-                assert!(into.len() > 0, "squeezed state is depleted!");
-                let output = into.drain(0..1).next().unwrap();
+                assert!(!into.is_empty(), "squeezed state is depleted!");
 
-                return output;
+                into.drain(0..1).next().unwrap()
             }
         }
     }
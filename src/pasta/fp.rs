@@ -0,0 +1,34 @@
+use crate::pairing::ff::{Field, PrimeField, PrimeFieldRepr};
+
+/// Base field of Pallas, scalar field of Vesta
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "28948022309329048855892746252171976963363056481941560715954676764349967630337"]
+#[PrimeFieldGenerator = "5"]
+pub struct Fp(FpRepr);
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::ff::{SqrtField, to_hex};
+    use crate::pasta::Fq;
+    use super::*;
+
+    #[test]
+    fn test_pasta_fields() {
+        assert_eq!(Fp::NUM_BITS, 255);
+        assert_eq!(Fp::S, 32);
+        assert_eq!(Fq::S, 32);
+        assert_eq!(format!("{}", Fp::char()), "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001");
+        assert_eq!(format!("{}", Fq::char()), "0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001");
+        assert_eq!(to_hex(&Fp::one()), "0000000000000000000000000000000000000000000000000000000000000001");
+
+        let mut minus_one = Fq::one();
+        minus_one.negate();
+        let mut square = Fq::from_str("12345").unwrap();
+        square.square();
+        let root = square.sqrt().unwrap();
+        let mut root_squared = root;
+        root_squared.square();
+        assert_eq!(root_squared, square);
+        assert!(minus_one.sqrt().is_some());
+    }
+}
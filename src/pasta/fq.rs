@@ -0,0 +1,7 @@
+use crate::pairing::ff::{Field, PrimeField, PrimeFieldRepr};
+
+/// Base field of Vesta, scalar field of Pallas
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "28948022309329048855892746252171976963363056481941647379679742748393362948097"]
+#[PrimeFieldGenerator = "5"]
+pub struct Fq(FqRepr);
@@ -0,0 +1,347 @@
+use crate::pairing::ff::PrimeField;
+use super::{PoseidonEngine, PoseidonHashParams, RoundConstantsPosition, small_sbox_exponent};
+
+pub const WIDTH: usize = 4;
+
+/// How the sbox is laid out in gates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SBoxGate {
+    /// Separate `a^alpha = b` row for every sbox, the linear layer
+    /// is made of linear gates
+    Naive,
+    /// Every row of the linear layer raises its inputs to `alpha` itself,
+    /// so a round is just one row per output element for t <= 3
+    Fused,
+}
+
+/// Selectors of the gate
+/// `q_a a + q_b b + q_c c + q_d d + q_m a b + q_const + q_pow_a a^alpha + q_pow_b b^alpha + q_pow_c c^alpha = 0`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selectors<F: PrimeField> {
+    pub linear: [F; WIDTH],
+    pub mul: F,
+    pub constant: F,
+    pub pow: [F; WIDTH - 1],
+}
+
+impl<F: PrimeField> Selectors<F> {
+    fn zero() -> Self {
+        Self {
+            linear: [F::zero(); WIDTH],
+            mul: F::zero(),
+            constant: F::zero(),
+            pow: [F::zero(); WIDTH - 1],
+        }
+    }
+
+    fn evaluate(&self, alpha: u64, values: &[F; WIDTH]) -> F {
+        let mut result = self.constant;
+        for (q, v) in self.linear.iter().zip(values.iter()) {
+            let mut tmp = *q;
+            tmp.mul_assign(v);
+            result.add_assign(&tmp);
+        }
+        for (q, v) in self.pow.iter().zip(values.iter()) {
+            if !q.is_zero() {
+                let mut tmp = v.pow([alpha]);
+                tmp.mul_assign(q);
+                result.add_assign(&tmp);
+            }
+        }
+        let mut tmp = self.mul;
+        tmp.mul_assign(&values[0]);
+        tmp.mul_assign(&values[1]);
+        result.add_assign(&tmp);
+
+        result
+    }
+}
+
+/// Row of the circuit. Wires hold variable indexes, `None` wires are unused
+/// and are assigned zero in the witness
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gate<F: PrimeField> {
+    pub selectors: Selectors<F>,
+    pub wires: [Option<usize>; WIDTH],
+    // wire that is computed by this row and only enters the equation linearly
+    output: usize,
+}
+
+/// Gate layout of one Poseidon permutation for width-4 PLONK
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonPlonkLayout<F: PrimeField> {
+    pub alpha: u64,
+    pub gates: Vec<Gate<F>>,
+    pub num_variables: usize,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+}
+
+struct LayoutBuilder<F: PrimeField> {
+    gates: Vec<Gate<F>>,
+    num_variables: usize,
+}
+
+impl<F: PrimeField> LayoutBuilder<F> {
+    fn alloc(&mut self) -> usize {
+        self.num_variables += 1;
+        self.num_variables - 1
+    }
+
+    // output = sum coeff_k * term_k(x_k) + constant, where term is x^alpha if flagged,
+    // long combinations are chained through the `c` wire
+    fn linear_combination(&mut self, terms: &[(usize, F, bool)], constant: F) -> usize {
+        let mut accumulator = None;
+        let mut terms = terms;
+        loop {
+            let capacity = if accumulator.is_some() { WIDTH - 2 } else { WIDTH - 1 };
+            let (chunk, rest) = terms.split_at(capacity.min(terms.len()));
+            let output = self.alloc();
+
+            let mut selectors = Selectors::zero();
+            let mut wires = [None; WIDTH];
+            for (i, &(var, coeff, is_pow)) in chunk.iter().enumerate() {
+                wires[i] = Some(var);
+                if is_pow {
+                    selectors.pow[i] = coeff;
+                } else {
+                    selectors.linear[i] = coeff;
+                }
+            }
+            if let Some(acc) = accumulator {
+                wires[WIDTH - 2] = Some(acc);
+                selectors.linear[WIDTH - 2] = F::one();
+            }
+            wires[WIDTH - 1] = Some(output);
+            selectors.linear[WIDTH - 1] = F::one();
+            selectors.linear[WIDTH - 1].negate();
+            if rest.is_empty() {
+                selectors.constant = constant;
+            }
+
+            self.gates.push(Gate { selectors, wires, output: WIDTH - 1 });
+            accumulator = Some(output);
+            terms = rest;
+            if terms.is_empty() {
+                return output;
+            }
+        }
+    }
+
+    // b = a^alpha
+    fn sbox(&mut self, input: usize) -> usize {
+        let output = self.alloc();
+        let mut selectors = Selectors::zero();
+        selectors.pow[0] = F::one();
+        selectors.linear[1] = F::one();
+        selectors.linear[1].negate();
+        self.gates.push(Gate { selectors, wires: [Some(input), Some(output), None, None], output: 1 });
+
+        output
+    }
+}
+
+impl<F: PrimeField> PoseidonPlonkLayout<F> {
+    pub fn new<E: PoseidonEngine<Fr = F>>(
+        params: &E::Params,
+        sbox_gate: SBoxGate
+    ) -> Self {
+        assert!(params.num_full_rounds() % 2 == 0);
+        assert!(params.round_constants_position() == RoundConstantsPosition::BeforeSBox, "round constants must be added before the sbox");
+        let alpha = small_sbox_exponent::<E, _>(params.sbox()).expect("sbox must be a low degree power map");
+        let width = params.state_width() as usize;
+        let half_of_full_rounds = params.num_full_rounds() / 2;
+        let partial_rounds_end = half_of_full_rounds + params.num_partial_rounds();
+        let num_rounds = params.num_full_rounds() + params.num_partial_rounds();
+
+        let mut builder = LayoutBuilder { gates: vec![], num_variables: 0 };
+        let inputs: Vec<_> = (0..width).map(|_| builder.alloc()).collect();
+
+        // constants of the first round are added explicitly, constants
+        // of every next round are merged into the linear layer of the previous one
+        let mut state: Vec<_> = inputs.iter().zip(params.round_constants(0).iter())
+            .map(|(&var, c)| builder.linear_combination(&[(var, F::one(), false)], *c))
+            .collect();
+
+        for round in 0..num_rounds {
+            let is_full_round = round < half_of_full_rounds || round >= partial_rounds_end;
+            let mut terms: Vec<_> = state.iter().enumerate().map(|(k, &var)| {
+                (var, is_full_round || k == params.partial_round_sbox_index() as usize)
+            }).collect();
+
+            if sbox_gate == SBoxGate::Naive {
+                for (var, is_pow) in terms.iter_mut() {
+                    if *is_pow {
+                        *var = builder.sbox(*var);
+                        *is_pow = false;
+                    }
+                }
+            }
+
+            state = (0..width).map(|j| {
+                let constant = if round + 1 < num_rounds {
+                    params.round_constants(round + 1)[j]
+                } else {
+                    F::zero()
+                };
+                let row_terms: Vec<_> = terms.iter().zip(params.mds_matrix_row(j as u32).iter())
+                    .map(|(&(var, is_pow), m)| (var, *m, is_pow))
+                    .collect();
+
+                builder.linear_combination(&row_terms, constant)
+            }).collect();
+        }
+
+        Self {
+            alpha,
+            gates: builder.gates,
+            num_variables: builder.num_variables,
+            inputs,
+            outputs: state
+        }
+    }
+
+    /// Pairs of `(row, column)` positions that must hold equal values. Every
+    /// use of a variable is connected to the previous one, so pairs form the
+    /// cycles of the permutation argument
+    pub fn copy_constraints(&self) -> Vec<((usize, usize), (usize, usize))> {
+        let mut last_position = vec![None; self.num_variables];
+        let mut constraints = vec![];
+        for (row, gate) in self.gates.iter().enumerate() {
+            for (column, wire) in gate.wires.iter().enumerate() {
+                if let Some(var) = *wire {
+                    if let Some(previous) = last_position[var] {
+                        constraints.push((previous, (row, column)));
+                    }
+                    last_position[var] = Some((row, column));
+                }
+            }
+        }
+
+        constraints
+    }
+
+    /// Per row wire values for one permutation of `input`
+    pub fn generate_witness(&self, input: &[F]) -> Vec<[F; WIDTH]> {
+        assert_eq!(input.len(), self.inputs.len());
+        let mut values = vec![F::zero(); self.num_variables];
+        for (&var, value) in self.inputs.iter().zip(input.iter()) {
+            values[var] = *value;
+        }
+
+        // every gate is linear in its output wire, so it's solved for it
+        let mut witness = Vec::with_capacity(self.gates.len());
+        for gate in self.gates.iter() {
+            let mut row = [F::zero(); WIDTH];
+            for (place_into, wire) in row.iter_mut().zip(gate.wires.iter()) {
+                if let Some(var) = *wire {
+                    *place_into = values[var];
+                }
+            }
+            row[gate.output] = F::zero();
+
+            let mut output = gate.selectors.evaluate(self.alpha, &row);
+            output.negate();
+            output.mul_assign(&gate.selectors.linear[gate.output].inverse().unwrap());
+            row[gate.output] = output;
+            values[gate.wires[gate.output].unwrap()] = output;
+
+            witness.push(row);
+        }
+
+        witness
+    }
+
+    pub fn output_values(&self, witness: &[[F; WIDTH]]) -> Vec<F> {
+        let mut values = vec![F::zero(); self.num_variables];
+        for (gate, row) in self.gates.iter().zip(witness.iter()) {
+            for (wire, value) in gate.wires.iter().zip(row.iter()) {
+                if let Some(var) = *wire {
+                    values[var] = *value;
+                }
+            }
+        }
+
+        self.outputs.iter().map(|&var| values[var]).collect()
+    }
+
+    /// Returns the first row that violates either its gate equation
+    /// or a copy constraint
+    pub fn find_violation(&self, witness: &[[F; WIDTH]]) -> Option<usize> {
+        assert_eq!(witness.len(), self.gates.len());
+        for (row, (gate, values)) in self.gates.iter().zip(witness.iter()).enumerate() {
+            if !gate.selectors.evaluate(self.alpha, values).is_zero() {
+                return Some(row);
+            }
+        }
+
+        self.copy_constraints().into_iter()
+            .filter(|&((r0, c0), (r1, c1))| witness[r0][c0] != witness[r1][c1])
+            .map(|(_, (row, _))| row)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::ff::Field;
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::poseidon_mimc;
+    use super::*;
+
+    #[test]
+    fn test_bn256_plonk_witness_matches_permutation() {
+        let rng = &mut thread_rng();
+        for params in [
+            Bn256PoseidonParams::new_checked_2_into_1(),
+            Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 3, 33, 8, 80)
+        ].iter() {
+            let input: Vec<Fr> = (0..params.state_width()).map(|_| rng.gen()).collect();
+            let expected = poseidon_mimc::<Bn256>(params, &input);
+
+            for sbox_gate in [SBoxGate::Naive, SBoxGate::Fused].iter() {
+                let layout = PoseidonPlonkLayout::new::<Bn256>(params, *sbox_gate);
+                let witness = layout.generate_witness(&input);
+                assert_eq!(layout.find_violation(&witness), None);
+                assert_eq!(layout.output_values(&witness), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bn256_plonk_gate_counts() {
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let rounds = (params.num_full_rounds() + params.num_partial_rounds()) as usize;
+
+        let fused = PoseidonPlonkLayout::<Fr>::new::<Bn256>(&params, SBoxGate::Fused);
+        assert_eq!(fused.gates.len(), 3 + 3 * rounds);
+
+        let naive = PoseidonPlonkLayout::<Fr>::new::<Bn256>(&params, SBoxGate::Naive);
+        let num_sboxes = 3 * params.num_full_rounds() + params.num_partial_rounds();
+        assert_eq!(naive.gates.len(), fused.gates.len() + num_sboxes as usize);
+    }
+
+    #[test]
+    fn test_bn256_plonk_detects_invalid_witness() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let layout = PoseidonPlonkLayout::new::<Bn256>(&params, SBoxGate::Naive);
+        let input: Vec<Fr> = (0..3).map(|_| rng.gen()).collect();
+        let witness = layout.generate_witness(&input);
+
+        // breaks the gate equation of the row
+        let mut broken = witness.clone();
+        broken[7][3].add_assign(&Fr::one());
+        assert_eq!(layout.find_violation(&broken), Some(7));
+
+        // fix the gate by changing the constant, so only the copy constraint fails
+        let mut broken = witness.clone();
+        let mut altered = layout.clone();
+        broken[7][3].add_assign(&Fr::one());
+        altered.gates[7].selectors.constant.add_assign(&Fr::one());
+        assert!(altered.find_violation(&broken).unwrap() > 7);
+    }
+}
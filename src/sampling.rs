@@ -0,0 +1,210 @@
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use num_bigint::BigUint;
+use rand::SeedableRng;
+use rand::chacha::ChaChaRng;
+
+use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+use crate::group_hash::GroupHasher;
+use crate::constants::GH_FIRST_BLOCK;
+use super::{biguint_to_repr, repr_to_biguint};
+
+/// How round constants are derived from the hasher output
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ConstantSampling {
+    /// Interprets one digest as a little endian field element and retries
+    /// with the next nonce if it's not in the field or is zero. Used for
+    /// all the parameters that were generated before
+    #[default]
+    Rejection,
+    /// Draws twice the size of the field representation (64 bytes for 256 bit reprs)
+    /// from consecutive digests and reduces it modulo the characteristic, so
+    /// every nonce produces a constant with a negligible bias
+    WideReduction,
+}
+
+/// How the 32 byte digest is turned into the seed of the MDS matrix RNG
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MdsSeedDerivation {
+    /// Eight consecutive big endian words of the digest
+    #[default]
+    BigEndianWords,
+    /// The first big endian word of the digest repeated eight times, as
+    /// franklin-crypto reads every word from the start of the digest
+    RepeatedFirstWord,
+}
+
+/// Inputs of the parameter generation. Default values are the ones all
+/// the existing parameters were generated with, custom tags or a first block
+/// give an independent family of parameters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamsSeeds {
+    pub round_constants_tag: Vec<u8>,
+    pub mds_tag: Vec<u8>,
+    pub first_block: Vec<u8>,
+    pub sampling: ConstantSampling,
+    pub mds_seed_derivation: MdsSeedDerivation,
+}
+
+impl Default for ParamsSeeds {
+    fn default() -> Self {
+        Self {
+            round_constants_tag: b"Rescue_f".to_vec(),
+            // This tag is a first one in a sequence of b"ResMxxxx"
+            // that produces MDS matrix without eigenvalues
+            // if we use Blake hasher
+            mds_tag: b"ResM0003".to_vec(),
+            first_block: GH_FIRST_BLOCK.to_vec(),
+            sampling: ConstantSampling::Rejection,
+            mds_seed_derivation: MdsSeedDerivation::BigEndianWords,
+        }
+    }
+}
+
+impl ParamsSeeds {
+    pub fn with_round_constants_tag(mut self, tag: &[u8]) -> Self {
+        self.round_constants_tag = tag.to_vec();
+        self
+    }
+
+    pub fn with_mds_tag(mut self, tag: &[u8]) -> Self {
+        self.mds_tag = tag.to_vec();
+        self
+    }
+
+    pub fn with_first_block(mut self, first_block: &[u8]) -> Self {
+        self.first_block = first_block.to_vec();
+        self
+    }
+
+    pub fn with_sampling(mut self, sampling: ConstantSampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    pub fn with_mds_seed_derivation(mut self, derivation: MdsSeedDerivation) -> Self {
+        self.mds_seed_derivation = derivation;
+        self
+    }
+
+    pub fn sample_round_constants<F: PrimeField, H: GroupHasher>(&self, num_constants: usize) -> Vec<F> {
+        sample_constants::<F, H>(self.sampling, &self.round_constants_tag, &self.first_block, num_constants)
+    }
+
+    /// RNG for the MDS matrix seeded by `H(mds_tag; first_block)`
+    pub fn mds_rng<H: GroupHasher>(&self) -> ChaChaRng {
+        let mut h = H::new(&self.mds_tag);
+        h.update(&self.first_block);
+        let h = h.finalize();
+        assert!(h.len() == 32);
+        let mut seed = [0u32; 8];
+        for (i, chunk) in h.chunks_exact(4).enumerate() {
+            let chunk = match self.mds_seed_derivation {
+                MdsSeedDerivation::BigEndianWords => chunk,
+                MdsSeedDerivation::RepeatedFirstWord => &h[..4],
+            };
+            seed[i] = (&chunk[..]).read_u32::<BigEndian>().expect("digest is large enough for this to work");
+        }
+
+        ChaChaRng::from_seed(&seed)
+    }
+}
+
+/// Derives `num_constants` non-zero field elements from
+/// `H(tag; first_block || nonce)` with a big endian `u32` nonce
+pub fn sample_constants<F: PrimeField, H: GroupHasher>(
+    sampling: ConstantSampling,
+    tag: &[u8],
+    first_block: &[u8],
+    num_constants: usize
+) -> Vec<F> {
+    let repr_len = F::Repr::default().as_ref().len() * 8;
+    let mut constants = Vec::with_capacity(num_constants);
+    let mut nonce = 0u32;
+    let mut nonce_bytes = [0u8; 4];
+
+    while constants.len() < num_constants {
+        (&mut nonce_bytes[0..4]).write_u32::<BigEndian>(nonce).unwrap();
+
+        let candidate = match sampling {
+            ConstantSampling::Rejection => {
+                let mut h = H::new(tag);
+                h.update(first_block);
+                h.update(&nonce_bytes[..]);
+                let h = h.finalize();
+                assert!(h.len() == repr_len, "digest length must match the field representation");
+
+                let mut constant_repr = F::Repr::default();
+                constant_repr.read_le(&h[..]).unwrap();
+
+                F::from_repr(constant_repr).ok()
+            },
+            ConstantSampling::WideReduction => {
+                // digests are domain separated by a one byte block counter
+                let mut wide = Vec::with_capacity(2 * repr_len);
+                let mut counter = 0u8;
+                while wide.len() < 2 * repr_len {
+                    let mut h = H::new(tag);
+                    h.update(first_block);
+                    h.update(&nonce_bytes[..]);
+                    h.update(&[counter]);
+                    wide.extend(h.finalize());
+                    counter += 1;
+                }
+                wide.truncate(2 * repr_len);
+
+                let modulus = repr_to_biguint::<F>(&F::char());
+                let reduced = BigUint::from_bytes_le(&wide) % modulus;
+
+                Some(F::from_repr(biguint_to_repr::<F>(&reduced)).unwrap())
+            }
+        };
+
+        if let Some(constant) = candidate {
+            if !constant.is_zero() {
+                constants.push(constant);
+            }
+        }
+
+        nonce += 1;
+    }
+
+    constants
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pairing::{bn256, bls12_381};
+    use crate::group_hash::{BlakeHasher, Sha256Hasher};
+    use super::*;
+
+    #[test]
+    fn test_wide_reduction_matches_definition() {
+        let constants = sample_constants::<bn256::Fr, BlakeHasher>(ConstantSampling::WideReduction, b"Rescue_f", GH_FIRST_BLOCK, 10);
+        assert_eq!(constants.len(), 10);
+
+        let mut wide = vec![];
+        for counter in 0..2u8 {
+            let mut h = BlakeHasher::new(b"Rescue_f");
+            h.update(GH_FIRST_BLOCK);
+            h.update(&[0, 0, 0, 1]);
+            h.update(&[counter]);
+            wide.extend(h.finalize());
+        }
+        let expected = BigUint::from_bytes_le(&wide) % repr_to_biguint::<bn256::Fr>(&bn256::Fr::char());
+        assert_eq!(repr_to_biguint::<bn256::Fr>(&constants[1].into_repr()), expected);
+
+        let legacy = sample_constants::<bn256::Fr, BlakeHasher>(ConstantSampling::Rejection, b"Rescue_f", GH_FIRST_BLOCK, 10);
+        assert!(legacy != constants);
+    }
+
+    #[test]
+    fn test_wide_reduction_for_other_fields() {
+        let first = sample_constants::<bls12_381::Fr, Sha256Hasher>(ConstantSampling::WideReduction, b"Tag", b"block", 20);
+        let second = sample_constants::<bls12_381::Fr, Sha256Hasher>(ConstantSampling::WideReduction, b"Tag", b"block", 30);
+        assert_eq!(first[..], second[..20]);
+
+        // no rejections, every nonce gives a constant
+        let other = sample_constants::<bls12_381::Fr, Sha256Hasher>(ConstantSampling::WideReduction, b"Tag", b"other", 20);
+        assert!(first.iter().all(|c| !other.contains(c)));
+    }
+}
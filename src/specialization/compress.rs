@@ -0,0 +1,284 @@
+use crate::pairing::ff::Field;
+use crate::{PoseidonEngine, PoseidonHashParams, RoundConstantsPosition, SBox};
+
+/// 2-into-1 compression for t = 3 params, e.g. for Merkle trees. Constants
+/// and matrices are copied out of params into fixed size arrays and the state
+/// lives on the stack. Partial rounds use the equivalent sparse matrices
+/// from the appendix B of the Poseidon paper: every partial round matrix `A`
+/// is factored as `A = M'' * M'` where `M' = diag(A_RR, 1)` commutes with
+/// the sbox on the last element and is merged into the previous round, and
+/// `M''` needs 5 multiplications instead of 9.
+/// Output is equal to `poseidon_hash` of `[left, right]`.
+pub struct Compress2Into1<E: PoseidonEngine> {
+    first_full_rounds: Vec<FullRound<E>>,
+    partial_rounds: Vec<PartialRound<E>>,
+    last_full_rounds: Vec<FullRound<E>>,
+    // transform of the input if there are no full rounds
+    // to merge the first `M'` into
+    input_transform: Option<[[E::Fr; 3]; 3]>,
+    sbox: <E::Params as PoseidonHashParams<E>>::SBox,
+}
+
+struct FullRound<E: PoseidonEngine> {
+    round_constants: [E::Fr; 3],
+    mds: [[E::Fr; 3]; 3],
+}
+
+// sparse matrix [[1, 0, a_0], [0, 1, a_1], [w_0, w_1, d]]
+struct PartialRound<E: PoseidonEngine> {
+    round_constants: [E::Fr; 3],
+    a: [E::Fr; 2],
+    w: [E::Fr; 2],
+    d: E::Fr,
+}
+
+impl<E: PoseidonEngine> Clone for FullRound<E> {
+    fn clone(&self) -> Self {
+        Self {
+            round_constants: self.round_constants,
+            mds: self.mds
+        }
+    }
+}
+
+impl<E: PoseidonEngine> Clone for PartialRound<E> {
+    fn clone(&self) -> Self {
+        Self {
+            round_constants: self.round_constants,
+            a: self.a,
+            w: self.w,
+            d: self.d
+        }
+    }
+}
+
+impl<E: PoseidonEngine> Clone for Compress2Into1<E> {
+    fn clone(&self) -> Self {
+        Self {
+            first_full_rounds: self.first_full_rounds.clone(),
+            partial_rounds: self.partial_rounds.clone(),
+            last_full_rounds: self.last_full_rounds.clone(),
+            input_transform: self.input_transform,
+            sbox: self.sbox.clone()
+        }
+    }
+}
+
+impl<E: PoseidonEngine> Compress2Into1<E> {
+    pub fn new(
+        params: &E::Params
+    ) -> Self {
+        assert!(params.rate() == 2, "rate is invalid for specialization");
+        assert!(params.capacity() == 1, "capacity is invalid for specialization");
+        assert!(params.num_full_rounds() % 2 == 0);
+        assert!(params.partial_round_sbox_index() == 2, "sbox of partial rounds must be applied to the last element");
+        assert!(params.round_constants_position() == RoundConstantsPosition::BeforeSBox, "round constants must be added before the sbox");
+
+        let half_of_full_rounds = params.num_full_rounds() / 2;
+        let num_partial_rounds = params.num_partial_rounds();
+
+        let mut mds = [[E::Fr::zero(); 3]; 3];
+        for (row, mds_row) in mds.iter_mut().enumerate() {
+            mds_row.copy_from_slice(params.mds_matrix_row(row as u32));
+        }
+
+        let round_constants = |round: u32| {
+            let c = params.round_constants(round);
+            [c[0], c[1], c[2]]
+        };
+
+        let mut first_full_rounds: Vec<_> = (0..half_of_full_rounds).map(|round| {
+            FullRound { round_constants: round_constants(round), mds }
+        }).collect();
+
+        let last_full_rounds = ((half_of_full_rounds + num_partial_rounds)..(params.num_full_rounds() + num_partial_rounds)).map(|round| {
+            FullRound { round_constants: round_constants(round), mds }
+        }).collect();
+
+        // walk partial rounds backwards, factor the matrix of the round and merge
+        // the dense block diagonal factor into the matrix of the previous round
+        let mut partial_rounds = Vec::with_capacity(num_partial_rounds as usize);
+        let mut input_transform = None;
+        let mut matrix = mds;
+        for k in (0..num_partial_rounds).rev() {
+            let (a_rr, a, w, d) = factor_sparse::<E>(&matrix);
+            let m_prime = [
+                [a_rr[0][0], a_rr[0][1], E::Fr::zero()],
+                [a_rr[1][0], a_rr[1][1], E::Fr::zero()],
+                [E::Fr::zero(), E::Fr::zero(), E::Fr::one()],
+            ];
+
+            let round_constants = mul_by_mds::<E>(&m_prime, &round_constants(half_of_full_rounds + k));
+            partial_rounds.push(PartialRound { round_constants, a, w, d });
+
+            if k == 0 {
+                match first_full_rounds.last_mut() {
+                    Some(round) => round.mds = mat_mul::<E>(&m_prime, &round.mds),
+                    None => input_transform = Some(m_prime)
+                }
+            } else {
+                matrix = mat_mul::<E>(&m_prime, &mds);
+            }
+        }
+        partial_rounds.reverse();
+
+        Self {
+            first_full_rounds,
+            partial_rounds,
+            last_full_rounds,
+            input_transform,
+            sbox: params.sbox().clone(),
+        }
+    }
+
+    pub fn compress(
+        &self,
+        left: E::Fr,
+        right: E::Fr
+    ) -> E::Fr {
+        let mut state = [left, right, E::Fr::zero()];
+        if let Some(transform) = self.input_transform.as_ref() {
+            state = mul_by_mds::<E>(transform, &state);
+        }
+
+        for round in self.first_full_rounds.iter() {
+            add_round_constants::<E>(&mut state, &round.round_constants);
+            self.sbox.apply(&mut state[..]);
+            state = mul_by_mds::<E>(&round.mds, &state);
+        }
+
+        for round in self.partial_rounds.iter() {
+            add_round_constants::<E>(&mut state, &round.round_constants);
+            self.sbox.apply(&mut state[2..]);
+
+            let last = state[2];
+            let mut new_last = round.d;
+            new_last.mul_assign(&last);
+            for ((s, a), w) in state[..2].iter_mut().zip(round.a.iter()).zip(round.w.iter()) {
+                let mut tmp = *w;
+                tmp.mul_assign(s);
+                new_last.add_assign(&tmp);
+
+                let mut tmp = *a;
+                tmp.mul_assign(&last);
+                s.add_assign(&tmp);
+            }
+            state[2] = new_last;
+        }
+
+        for round in self.last_full_rounds.iter() {
+            add_round_constants::<E>(&mut state, &round.round_constants);
+            self.sbox.apply(&mut state[..]);
+            state = mul_by_mds::<E>(&round.mds, &state);
+        }
+
+        state[0]
+    }
+}
+
+// Factors `m = M'' * diag(m_RR, 1)` where R are the first two coordinates.
+// Returns `m_RR` and the sparse part `[[1, 0, a_0], [0, 1, a_1], [w_0, w_1, d]]`,
+// so `a = m_Rp`, `w = m_pR * m_RR^-1` and `d = m_pp`.
+#[allow(clippy::type_complexity)]
+fn factor_sparse<E: PoseidonEngine>(m: &[[E::Fr; 3]; 3]) -> ([[E::Fr; 2]; 2], [E::Fr; 2], [E::Fr; 2], E::Fr) {
+    let a_rr = [[m[0][0], m[0][1]], [m[1][0], m[1][1]]];
+
+    let mut det = a_rr[0][0];
+    det.mul_assign(&a_rr[1][1]);
+    let mut tmp = a_rr[0][1];
+    tmp.mul_assign(&a_rr[1][0]);
+    det.sub_assign(&tmp);
+    let det_inv = det.inverse().expect("submatrix of the MDS matrix must be invertible");
+
+    let mut inv = [[a_rr[1][1], a_rr[0][1]], [a_rr[1][0], a_rr[0][0]]];
+    inv[0][1].negate();
+    inv[1][0].negate();
+    for el in inv.iter_mut().flat_map(|row| row.iter_mut()) {
+        el.mul_assign(&det_inv);
+    }
+
+    let mut w = [E::Fr::zero(); 2];
+    for (j, w) in w.iter_mut().enumerate() {
+        for k in 0..2 {
+            let mut tmp = m[2][k];
+            tmp.mul_assign(&inv[k][j]);
+            w.add_assign(&tmp);
+        }
+    }
+
+    (a_rr, [m[0][2], m[1][2]], w, m[2][2])
+}
+
+fn mat_mul<E: PoseidonEngine>(a: &[[E::Fr; 3]; 3], b: &[[E::Fr; 3]; 3]) -> [[E::Fr; 3]; 3] {
+    let mut result = [[E::Fr::zero(); 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            for k in 0..3 {
+                let mut tmp = a[i][k];
+                tmp.mul_assign(&b[k][j]);
+                result[i][j].add_assign(&tmp);
+            }
+        }
+    }
+
+    result
+}
+
+#[inline(always)]
+fn add_round_constants<E: PoseidonEngine>(state: &mut [E::Fr; 3], round_constants: &[E::Fr; 3]) {
+    state[0].add_assign(&round_constants[0]);
+    state[1].add_assign(&round_constants[1]);
+    state[2].add_assign(&round_constants[2]);
+}
+
+#[inline(always)]
+fn mul_by_mds<E: PoseidonEngine>(mds: &[[E::Fr; 3]; 3], state: &[E::Fr; 3]) -> [E::Fr; 3] {
+    [
+        row_product::<E>(&mds[0], state),
+        row_product::<E>(&mds[1], state),
+        row_product::<E>(&mds[2], state),
+    ]
+}
+
+#[inline(always)]
+fn row_product<E: PoseidonEngine>(row: &[E::Fr; 3], state: &[E::Fr; 3]) -> E::Fr {
+    let mut result = row[0];
+    result.mul_assign(&state[0]);
+
+    let mut tmp = row[1];
+    tmp.mul_assign(&state[1]);
+    result.add_assign(&tmp);
+
+    let mut tmp = row[2];
+    tmp.mul_assign(&state[2]);
+    result.add_assign(&tmp);
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::poseidon_hash;
+    use super::*;
+
+    #[test]
+    fn test_bn256_compress_matches_poseidon_hash() {
+        let rng = &mut thread_rng();
+        let checked = Bn256PoseidonParams::new_checked_2_into_1();
+        let reduced = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 33, 8, 80);
+        let no_full_rounds = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 10, 0, 80);
+
+        for params in [checked, reduced, no_full_rounds].iter() {
+            let compressor = Compress2Into1::<Bn256>::new(params);
+            for _ in 0..10 {
+                let left: Fr = rng.gen();
+                let right: Fr = rng.gen();
+                assert_eq!(vec![compressor.compress(left, right)], poseidon_hash::<Bn256>(params, &[left, right]));
+            }
+        }
+    }
+}
@@ -1,2 +1,7 @@
-pub mod specialization_macro;
+pub mod sponge;
 pub mod specialized_2_into_1;
+pub mod compress;
+
+pub use self::sponge::PoseidonSponge;
+pub use self::specialized_2_into_1::PosendonR2C1;
+pub use self::compress::Compress2Into1;
@@ -1,6 +1,33 @@
-#[macro_use]
-use super::specialization_macro::*;
+use super::sponge::PoseidonSponge;
 
-// construct_sponge! {
-// 	pub struct PosendonR2C1(2, 1);
-// }
\ No newline at end of file
+pub type PosendonR2C1<'a, E> = PoseidonSponge<'a, E, 2, 1>;
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::poseidon_hash;
+    use super::*;
+
+    #[test]
+    fn test_bn256_r2c1_matches_poseidon_hash() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+
+        for len in 0..7 {
+            let input: Vec<Fr> = (0..len).map(|_| rng.gen()).collect();
+
+            let mut sponge = PosendonR2C1::<Bn256>::new(&params);
+            sponge.absorb(&input);
+            assert_eq!(vec![sponge.squeeze_out_single()], poseidon_hash::<Bn256>(&params, &input));
+
+            // values absorbed one by one are padded on squeeze
+            let mut sponge = PosendonR2C1::<Bn256>::new(&params);
+            for &value in input.iter() {
+                sponge.absorb_single_value(value);
+            }
+            assert_eq!(vec![sponge.squeeze_out_single()], poseidon_hash::<Bn256>(&params, &input));
+        }
+    }
+}
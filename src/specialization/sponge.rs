@@ -0,0 +1,211 @@
+use crate::pairing::ff::Field;
+use crate::{PoseidonEngine, PoseidonHashParams, ParamsError, check_params, poseidon_mimc_in_parts};
+
+/// Sponge for a fixed rate and capacity that keeps all of its state
+/// in arrays and never allocates. Rate and capacity parts are stored
+/// separately because their sum can't be used as an array length.
+/// Output is identical to `StatefulSponge` for the same sequence of calls.
+pub struct PoseidonSponge<'a, E: PoseidonEngine, const RATE: usize, const CAPACITY: usize> {
+    params: &'a E::Params,
+    rate_state: [E::Fr; RATE],
+    capacity_state: [E::Fr; CAPACITY],
+    buffer: [E::Fr; RATE],
+    // number of absorbed elements in the buffer or number of
+    // already squeezed elements of the rate part
+    position: usize,
+    squeezing: bool,
+}
+
+impl<'a, E: PoseidonEngine, const RATE: usize, const CAPACITY: usize> Clone for PoseidonSponge<'a, E, RATE, CAPACITY> {
+    fn clone(&self) -> Self {
+        Self {
+            params: self.params,
+            rate_state: self.rate_state,
+            capacity_state: self.capacity_state,
+            buffer: self.buffer,
+            position: self.position,
+            squeezing: self.squeezing
+        }
+    }
+}
+
+impl<'a, E: PoseidonEngine, const RATE: usize, const CAPACITY: usize> PoseidonSponge<'a, E, RATE, CAPACITY> {
+    pub fn new(
+        params: &'a E::Params
+    ) -> Self {
+        assert!(params.rate() as usize == RATE, "rate is invalid for specialization");
+        assert!(params.capacity() as usize == CAPACITY, "capacity is invalid for specialization");
+
+        Self {
+            params,
+            rate_state: [E::Fr::zero(); RATE],
+            capacity_state: [E::Fr::zero(); CAPACITY],
+            buffer: [E::Fr::zero(); RATE],
+            position: 0,
+            squeezing: false,
+        }
+    }
+
+    /// Same as `new`, but returns an error for invalid params
+    /// or params of a different shape
+    pub fn try_new(
+        params: &'a E::Params
+    ) -> Result<Self, ParamsError> {
+        check_params(params)?;
+        if params.rate() as usize != RATE || params.capacity() as usize != CAPACITY {
+            return Err(ParamsError::ShapeMismatch {
+                expected_rate: RATE,
+                expected_capacity: CAPACITY,
+                rate: params.rate(),
+                capacity: params.capacity()
+            });
+        }
+
+        Ok(Self::new(params))
+    }
+
+    fn permute(&mut self) {
+        let mut rate_scratch = [E::Fr::zero(); RATE];
+        let mut capacity_scratch = [E::Fr::zero(); CAPACITY];
+
+        poseidon_mimc_in_parts::<E>(
+            self.params,
+            (&mut self.rate_state[..], &mut self.capacity_state[..]),
+            (&mut rate_scratch[..], &mut capacity_scratch[..])
+        );
+    }
+
+    fn absorb_buffer(&mut self) {
+        for (s, v) in self.rate_state.iter_mut().zip(self.buffer.iter()) {
+            s.add_assign(v);
+        }
+        self.permute();
+    }
+
+    pub fn absorb_single_value(
+        &mut self,
+        value: E::Fr
+    ) {
+        if self.squeezing {
+            // we don't need anything from the output, so it's dropped
+            self.squeezing = false;
+            self.position = 0;
+        } else if self.position == RATE {
+            self.absorb_buffer();
+            self.position = 0;
+        }
+
+        self.buffer[self.position] = value;
+        self.position += 1;
+    }
+
+    pub fn absorb(
+        &mut self,
+        input: &[E::Fr]
+    ) {
+        for &val in input.iter() {
+            self.absorb_single_value(val);
+        }
+
+        if input.len() % RATE != 0 {
+            for _ in 0..(RATE - input.len() % RATE) {
+                self.absorb_single_value(E::Fr::one());
+            }
+        }
+    }
+
+    pub fn squeeze_out_single(
+        &mut self,
+    ) -> E::Fr {
+        if !self.squeezing {
+            for el in self.buffer[self.position..].iter_mut() {
+                *el = E::Fr::one();
+            }
+            self.absorb_buffer();
+            self.squeezing = true;
+            self.position = 0;
+        } else if self.position == RATE {
+            self.permute();
+            self.position = 0;
+        }
+
+        let output = self.rate_state[self.position];
+        self.position += 1;
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::{StatefulSponge, poseidon_mimc, permute};
+    use super::*;
+
+    #[test]
+    fn test_bn256_permute_matches_dynamic() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let mut state: [Fr; 3] = rng.gen();
+        let expected = poseidon_mimc::<Bn256>(&params, &state);
+
+        permute::<Bn256, 3>(&params, &mut state);
+        assert_eq!(&state[..], &expected[..]);
+    }
+
+    fn check_against_dynamic<const RATE: usize, const CAPACITY: usize>(params: &Bn256PoseidonParams) {
+        let rng = &mut thread_rng();
+
+        // plain absorb of every length up to a few full absorbtion cycles
+        for len in 0..(3 * RATE + 2) {
+            let input: Vec<Fr> = (0..len).map(|_| rng.gen()).collect();
+
+            let mut dynamic = StatefulSponge::<Bn256>::new(params);
+            dynamic.absorb(&input);
+            let mut fixed = PoseidonSponge::<Bn256, RATE, CAPACITY>::new(params);
+            fixed.absorb(&input);
+
+            for _ in 0..(2 * RATE + 1) {
+                assert_eq!(fixed.squeeze_out_single(), dynamic.squeeze_out_single());
+            }
+        }
+
+        // random interleaving of all operations
+        for _ in 0..20 {
+            let mut dynamic = StatefulSponge::<Bn256>::new(params);
+            let mut fixed = PoseidonSponge::<Bn256, RATE, CAPACITY>::new(params);
+
+            for _ in 0..12 {
+                match rng.gen_range(0, 3) {
+                    0 => {
+                        let value: Fr = rng.gen();
+                        dynamic.absorb_single_value(value);
+                        fixed.absorb_single_value(value);
+                    },
+                    1 => {
+                        let len = rng.gen_range(0, 2 * RATE + 1);
+                        let input: Vec<Fr> = (0..len).map(|_| rng.gen()).collect();
+                        dynamic.absorb(&input);
+                        fixed.absorb(&input);
+                    },
+                    _ => {
+                        assert_eq!(fixed.squeeze_out_single(), dynamic.squeeze_out_single());
+                    }
+                }
+            }
+
+            assert_eq!(fixed.squeeze_out_single(), dynamic.squeeze_out_single());
+        }
+    }
+
+    #[test]
+    fn test_bn256_fixed_sponge_matches_dynamic() {
+        check_against_dynamic::<2, 1>(&Bn256PoseidonParams::new_2_into_1::<BlakeHasher>());
+        check_against_dynamic::<3, 1>(&Bn256PoseidonParams::new_3_into_1::<BlakeHasher>());
+        check_against_dynamic::<4, 2>(&Bn256PoseidonParams::new_for_params::<BlakeHasher>(2, 4, 33, 8, 80));
+        check_against_dynamic::<1, 3>(&Bn256PoseidonParams::new_for_params::<BlakeHasher>(3, 1, 33, 8, 80));
+    }
+}
@@ -0,0 +1,7 @@
+use crate::pairing::ff::{Field, PrimeField, PrimeFieldRepr};
+
+/// Starknet field, `p = 2^251 + 17 * 2^192 + 1`
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "3618502788666131213697322783095070105623107215331596699973092056135872020481"]
+#[PrimeFieldGenerator = "3"]
+pub struct Fr(FrRepr);
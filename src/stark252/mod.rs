@@ -0,0 +1,235 @@
+use crate::pairing::ff::{Field, PrimeField, ScalarEngine};
+use sha2::{Digest, Sha256};
+use super::{PoseidonEngine, PoseidonHashParams, PoseidonParamsInternal, CubicSBox, biguint_to_repr, poseidon_mimc, repr_to_biguint};
+
+// code generated by the `PrimeField` derive
+#[allow(clippy::too_many_arguments)]
+mod fr;
+pub use self::fr::{Fr, FrRepr};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Stark252;
+
+impl ScalarEngine for Stark252 {
+    type Fr = Fr;
+}
+
+impl PoseidonEngine for Stark252 {
+    type Params = Stark252PoseidonParams;
+}
+
+#[derive(Clone)]
+pub struct Stark252PoseidonParams {
+    pub(crate) c: u32,
+    pub(crate) r: u32,
+    pub(crate) full_rounds: u32,
+    pub(crate) partial_rounds: u32,
+    pub(crate) round_constants: Vec<Fr>,
+    pub(crate) mds_matrix: Vec<Fr>,
+    pub(crate) security_level: u32,
+    pub(crate) sbox: CubicSBox<Stark252>,
+}
+
+/// `sha256(name || idx)` as a big endian number reduced modulo the characteristic,
+/// the way StarkWare's `generate_round_constant` does
+fn hades_round_constant(name: &str, idx: usize) -> Fr {
+    let digest = Sha256::digest(format!("{}{}", name, idx).as_bytes());
+    let value = num_bigint::BigUint::from_bytes_be(&digest) % repr_to_biguint::<Fr>(&Fr::char());
+
+    Fr::from_repr(biguint_to_repr::<Fr>(&value)).unwrap()
+}
+
+impl Stark252PoseidonParams {
+    /// Starknet's Poseidon (Hades permutation): width 3, rate 2, `x^3`,
+    /// 8 full and 83 partial rounds
+    pub fn new_starknet() -> Self {
+        let t = 3u32;
+        let full_rounds = 8u32;
+        let partial_rounds = 83u32;
+        let round_constants = (0..((full_rounds + partial_rounds) * t) as usize)
+            .map(|idx| hades_round_constant("Hades", idx))
+            .collect();
+
+        let mut minus_one = Fr::one();
+        minus_one.negate();
+        let mut minus_two = minus_one;
+        minus_two.double();
+        let one = Fr::one();
+        let three = Fr::from_str("3").unwrap();
+        let mds_matrix = vec![
+            three, one, one,
+            one, minus_one, one,
+            one, one, minus_two,
+        ];
+
+        Self {
+            c: 1,
+            r: 2,
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds_matrix,
+            security_level: 128,
+            sbox: CubicSBox { _marker: std::marker::PhantomData },
+        }
+    }
+}
+
+impl PoseidonParamsInternal<Stark252> for Stark252PoseidonParams {
+    fn set_round_constants(&mut self, to: Vec<Fr>) {
+        assert_eq!(self.round_constants.len(), to.len());
+        self.round_constants = to;
+    }
+}
+
+impl PoseidonHashParams<Stark252> for Stark252PoseidonParams {
+    type SBox = CubicSBox<Stark252>;
+
+    fn capacity(&self) -> u32 {
+        self.c
+    }
+    fn rate(&self) -> u32 {
+        self.r
+    }
+    fn num_full_rounds(&self) -> u32 {
+        self.full_rounds
+    }
+    fn num_partial_rounds(&self) -> u32 {
+        self.partial_rounds
+    }
+    fn round_constants(&self, round: u32) -> &[Fr] {
+        let t = self.c + self.r;
+        let start = (t*round) as usize;
+        let end = (t*(round+1)) as usize;
+
+        &self.round_constants[start..end]
+    }
+    fn mds_matrix_row(&self, row: u32) -> &[Fr] {
+        let t = self.c + self.r;
+        let start = (t*row) as usize;
+        let end = (t*(row+1)) as usize;
+
+        &self.mds_matrix[start..end]
+    }
+    fn security_level(&self) -> u32 {
+        self.security_level
+    }
+    fn output_len(&self) -> u32 {
+        1
+    }
+
+    fn sbox(&self) -> &Self::SBox {
+        &self.sbox
+    }
+}
+
+fn starknet_params() -> &'static Stark252PoseidonParams {
+    static PARAMS: std::sync::OnceLock<Stark252PoseidonParams> = std::sync::OnceLock::new();
+    PARAMS.get_or_init(Stark252PoseidonParams::new_starknet)
+}
+
+fn element(value: u64) -> Fr {
+    let mut repr = FrRepr::default();
+    repr.as_mut()[0] = value;
+
+    Fr::from_repr(repr).unwrap()
+}
+
+/// Starknet's `poseidon_hash(x, y)`, the capacity element is set to 2
+pub fn hash(x: Fr, y: Fr) -> Fr {
+    poseidon_mimc::<Stark252>(starknet_params(), &[x, y, element(2)])[0]
+}
+
+/// Starknet's `poseidon_hash_single(x)`, the capacity element is set to 1
+pub fn hash_single(x: Fr) -> Fr {
+    poseidon_mimc::<Stark252>(starknet_params(), &[x, Fr::zero(), element(1)])[0]
+}
+
+/// Starknet's `poseidon_hash_many`: the input is padded with 1 and then
+/// with zeroes to a multiple of the rate and absorbed from a zero state
+pub fn hash_many(inputs: &[Fr]) -> Fr {
+    let params = starknet_params();
+    let mut padded = inputs.to_vec();
+    padded.push(Fr::one());
+    if padded.len() % 2 == 1 {
+        padded.push(Fr::zero());
+    }
+
+    let mut state = vec![Fr::zero(); 3];
+    for chunk in padded.chunks(2) {
+        state[0].add_assign(&chunk[0]);
+        state[1].add_assign(&chunk[1]);
+        state = poseidon_mimc::<Stark252>(params, &state);
+    }
+
+    state[0]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_starknet_params() {
+        let params = starknet_params();
+        assert_eq!(Fr::NUM_BITS, 252);
+        assert_eq!(params.round_constants.len(), 3 * 91);
+        assert_eq!(
+            format!("{}", params.round_constants(0)[0].into_repr()),
+            "0x06861759ea556a2339dd92f9562a30b9e58e2ad98109ae4780b7fd8eac77fe6f"
+        );
+    }
+
+    #[test]
+    fn test_starknet_hash() {
+        assert_eq!(
+            format!("{}", hash(element(1), element(2)).into_repr()),
+            "0x05d44a3decb2b2e0cc71071f7b802f45dd792d064f0fc7316c46514f70f9891a"
+        );
+    }
+
+    #[test]
+    fn test_starknet_hash_many_padding() {
+        let params = starknet_params();
+        let (a, b, c) = (element(7), element(8), element(9));
+
+        // odd length is padded with a single 1
+        let state = poseidon_mimc::<Stark252>(params, &[a, b, Fr::zero()]);
+        let mut next = state.clone();
+        next[0].add_assign(&c);
+        next[1].add_assign(&Fr::one());
+        assert_eq!(hash_many(&[a, b, c]), poseidon_mimc::<Stark252>(params, &next)[0]);
+
+        // even length gets a whole padding block
+        let mut next = state;
+        next[0].add_assign(&Fr::one());
+        assert_eq!(hash_many(&[a, b]), poseidon_mimc::<Stark252>(params, &next)[0]);
+
+        assert_eq!(hash_many(&[]), poseidon_mimc::<Stark252>(params, &[Fr::one(), Fr::zero(), Fr::zero()])[0]);
+        assert!(hash_many(&[a]) != hash_single(a));
+    }
+
+    #[test]
+    fn test_starknet_crypto_vectors() {
+        // `poseidon_hash_single` and `poseidon_hash_many` of starknet-crypto 0.8.1
+        assert_eq!(
+            format!("{}", hash_single(element(1)).into_repr()),
+            "0x06d226d4c804cd74567f5ac59c6a4af1fe2a6eced19fb7560a9124579877da25"
+        );
+        assert_eq!(
+            format!("{}", hash_single(element(42)).into_repr()),
+            "0x002568401936f056c7ebbaebb44bf9b3b8a80abf66b89c735ff94f0efa44791e"
+        );
+
+        let inputs = [element(1), element(2), element(3)];
+        let vectors = [
+            "0x02272be0f580fd156823304800919530eaa97430e972d7213ee13f4fbf7a5dbc",
+            "0x00579e8877c7755365d5ec1ec7d3a94a457eff5d1f40482bbe9729c064cdead2",
+            "0x0371cb6995ea5e7effcd2e174de264b5b407027a75a231a70c2c8d196107f0e7",
+            "0x02f0d8840bcf3bc629598d8a6cc80cb7c0d9e52d93dab244bbf9cd0dca0ad082",
+        ];
+        for (len, expected) in vectors.iter().enumerate() {
+            assert_eq!(format!("{}", hash_many(&inputs[..len]).into_repr()), *expected, "{} inputs", len);
+        }
+    }
+}
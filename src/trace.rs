@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use crate::pairing::ff::{Field, PrimeField};
+use super::{PoseidonEngine, PoseidonHashParams, RoundConstantsPosition, SBox, add_round_constants, mul_by_mds, repr_to_biguint};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundKind {
+    Full,
+    Partial,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceStep {
+    AddRoundConstants,
+    SBox,
+    Mds,
+}
+
+/// How field elements are printed in exported traces. circom and most
+/// JS tooling print decimals, Rust implementations usually print hex
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementFormat {
+    Hex,
+    Decimal,
+}
+
+impl RoundKind {
+    fn name(&self) -> &'static str {
+        match self {
+            RoundKind::Full => "full",
+            RoundKind::Partial => "partial",
+        }
+    }
+}
+
+impl TraceStep {
+    fn name(&self) -> &'static str {
+        match self {
+            TraceStep::AddRoundConstants => "ark",
+            TraceStep::SBox => "sbox",
+            TraceStep::Mds => "mds",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceRow<F: PrimeField> {
+    pub round: u32,
+    pub kind: RoundKind,
+    pub step: TraceStep,
+    pub state: Vec<F>,
+}
+
+/// States of the permutation after every step of every round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermutationTrace<F: PrimeField> {
+    pub input: Vec<F>,
+    pub rows: Vec<TraceRow<F>>,
+}
+
+fn format_element<F: PrimeField>(el: &F, format: ElementFormat) -> String {
+    match format {
+        ElementFormat::Hex => format!("{}", el.into_repr()),
+        ElementFormat::Decimal => repr_to_biguint::<F>(&el.into_repr()).to_string(),
+    }
+}
+
+impl<F: PrimeField> PermutationTrace<F> {
+    pub fn output(&self) -> &[F] {
+        match self.rows.last() {
+            Some(row) => &row.state,
+            None => &self.input
+        }
+    }
+
+    /// Single JSON object with the input, the output and a flat list of steps
+    pub fn to_json(&self, format: ElementFormat) -> String {
+        let state_to_json = |state: &[F]| {
+            let elements: Vec<_> = state.iter().map(|el| format!("\"{}\"", format_element(el, format))).collect();
+            format!("[{}]", elements.join(","))
+        };
+
+        let mut json = String::new();
+        write!(json, "{{\"input\":{},\"steps\":[", state_to_json(&self.input)).unwrap();
+        for (i, row) in self.rows.iter().enumerate() {
+            if i != 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"round\":{},\"kind\":\"{}\",\"step\":\"{}\",\"state\":{}}}",
+                row.round,
+                row.kind.name(),
+                row.step.name(),
+                state_to_json(&row.state)
+            ).unwrap();
+        }
+        write!(json, "],\"output\":{}}}", state_to_json(self.output())).unwrap();
+
+        json
+    }
+
+    /// CSV with a header, one line per step and the input as the first line
+    /// with empty round and kind columns
+    pub fn to_csv(&self, format: ElementFormat) -> String {
+        let state_to_csv = |state: &[F]| {
+            let elements: Vec<_> = state.iter().map(|el| format_element(el, format)).collect();
+            elements.join(",")
+        };
+
+        let mut csv = String::from("round,kind,step");
+        for i in 0..self.input.len() {
+            write!(csv, ",state_{}", i).unwrap();
+        }
+        csv.push('\n');
+
+        writeln!(csv, ",,input,{}", state_to_csv(&self.input)).unwrap();
+        for row in self.rows.iter() {
+            writeln!(csv, "{},{},{},{}", row.round, row.kind.name(), row.step.name(), state_to_csv(&row.state)).unwrap();
+        }
+
+        csv
+    }
+}
+
+/// Same as `poseidon_mimc`, but records the state after every step
+pub fn poseidon_mimc_with_trace<E: PoseidonEngine>(
+    params: &E::Params,
+    old_state: &[E::Fr]
+) -> PermutationTrace<E::Fr> {
+    assert_eq!(old_state.len(), params.state_width() as usize);
+    debug_assert!(params.num_full_rounds() % 2 == 0 || params.num_partial_rounds() == 0);
+    let constants_first = params.round_constants_position() == RoundConstantsPosition::BeforeSBox;
+    let half_of_full_rounds = params.num_full_rounds() / 2;
+    let partial_rounds_end = half_of_full_rounds + params.num_partial_rounds();
+    let num_rounds = params.num_full_rounds() + params.num_partial_rounds();
+
+    let mut state = old_state.to_vec();
+    let mut mds_application_scratch = vec![E::Fr::zero(); state.len()];
+    let sbox_idx = params.partial_round_sbox_index() as usize;
+    let mut rows = Vec::with_capacity(3 * num_rounds as usize);
+
+    for round in 0..num_rounds {
+        let kind = if round < half_of_full_rounds || round >= partial_rounds_end {
+            RoundKind::Full
+        } else {
+            RoundKind::Partial
+        };
+
+        if constants_first {
+            add_round_constants::<E>(params.round_constants(round), &mut state, &mut []);
+            rows.push(TraceRow { round, kind, step: TraceStep::AddRoundConstants, state: state.clone() });
+        }
+
+        match kind {
+            RoundKind::Full => params.sbox().apply(&mut state),
+            RoundKind::Partial => params.sbox().apply(&mut state[sbox_idx..(sbox_idx + 1)]),
+        }
+        rows.push(TraceRow { round, kind, step: TraceStep::SBox, state: state.clone() });
+
+        mul_by_mds::<E>(params, (&mut state, &mut []), (&mut mds_application_scratch, &mut []));
+        rows.push(TraceRow { round, kind, step: TraceStep::Mds, state: state.clone() });
+
+        if !constants_first {
+            add_round_constants::<E>(params.round_constants(round), &mut state, &mut []);
+            rows.push(TraceRow { round, kind, step: TraceStep::AddRoundConstants, state: state.clone() });
+        }
+    }
+
+    PermutationTrace {
+        input: old_state.to_vec(),
+        rows
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, thread_rng};
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::poseidon_mimc;
+    use super::*;
+
+    #[test]
+    fn test_bn256_trace_matches_permutation() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_checked_2_into_1();
+        let state: Vec<Fr> = (0..3).map(|_| rng.gen()).collect();
+
+        let trace = poseidon_mimc_with_trace::<Bn256>(&params, &state);
+        assert_eq!(trace.rows.len(), 3 * (params.num_full_rounds() + params.num_partial_rounds()) as usize);
+        assert_eq!(trace.output(), &poseidon_mimc::<Bn256>(&params, &state)[..]);
+
+        // partial rounds only touch the last element with the sbox
+        let partial: Vec<_> = trace.rows.iter().filter(|row| row.kind == RoundKind::Partial).collect();
+        assert_eq!(partial.len(), 3 * params.num_partial_rounds() as usize);
+        assert_eq!(partial[0].state[..2], partial[1].state[..2]);
+        assert!(partial[0].state[2] != partial[1].state[2]);
+    }
+
+    #[test]
+    fn test_bn256_trace_export() {
+        let params = Bn256PoseidonParams::new_for_params::<BlakeHasher>(1, 2, 1, 2, 80);
+        let trace = poseidon_mimc_with_trace::<Bn256>(&params, &[Fr::zero(), Fr::one(), Fr::zero()]);
+
+        let csv = trace.to_csv(ElementFormat::Decimal);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2 + 3 * 3);
+        assert_eq!(lines[0], "round,kind,step,state_0,state_1,state_2");
+        assert_eq!(lines[1], ",,input,0,1,0");
+        assert!(lines[2].starts_with("0,full,ark,"));
+        assert!(lines[5].starts_with("1,partial,ark,"));
+        assert!(lines[10].starts_with("2,full,mds,"));
+
+        let json = trace.to_json(ElementFormat::Hex);
+        assert!(json.starts_with(&format!("{{\"input\":[\"{}\",", Fr::zero().into_repr())));
+        assert_eq!(json.matches("\"round\":").count(), 9);
+        assert!(json.ends_with(&format!("\"{}\"]}}", trace.output()[2].into_repr())));
+    }
+}
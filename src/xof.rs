@@ -0,0 +1,249 @@
+use crate::pairing::ff::{PrimeField, PrimeFieldRepr};
+use crate::rand::{Rng, SeedableRng};
+use super::{PoseidonEngine, StatefulSponge, biguint_to_repr, repr_to_biguint};
+
+/// Extendable output reader over a sponge that has finished absorbing.
+/// Elements are produced by `squeeze_out_single`, and byte output takes the
+/// lowest `Fr::CAPACITY / 8` bytes of every element in little-endian form.
+/// The characteristic is not a multiple of `2^(8 * bytes_per_element)`, so
+/// elements at or above the largest such multiple below it are skipped to keep
+/// the bytes uniform (about 0.8% of elements for bn256). Requesting an element
+/// drops any bytes left over from a partially consumed one.
+pub struct PoseidonXof<'a, E: PoseidonEngine> {
+    sponge: StatefulSponge<'a, E>,
+    buffer: Vec<u8>,
+    position: usize,
+    byte_output_bound: <E::Fr as PrimeField>::Repr,
+}
+
+impl<'a, E: PoseidonEngine> Clone for PoseidonXof<'a, E> {
+    fn clone(&self) -> Self {
+        Self {
+            sponge: self.sponge.clone(),
+            buffer: self.buffer.clone(),
+            position: self.position,
+            byte_output_bound: self.byte_output_bound
+        }
+    }
+}
+
+impl<'a, E: PoseidonEngine> PoseidonXof<'a, E> {
+    pub fn new(
+        sponge: StatefulSponge<'a, E>
+    ) -> Self {
+        // largest multiple of `2^(8 * bytes_per_element)` below the characteristic
+        let bits = 8 * Self::bytes_per_element();
+        let modulus = repr_to_biguint::<E::Fr>(&E::Fr::char());
+        let bound = (modulus >> bits) << bits;
+
+        Self {
+            sponge,
+            buffer: vec![],
+            position: 0,
+            byte_output_bound: biguint_to_repr::<E::Fr>(&bound),
+        }
+    }
+
+    pub fn from_input(
+        params: &'a E::Params,
+        input: &[E::Fr]
+    ) -> Self {
+        let mut sponge = StatefulSponge::<E>::new(params);
+        sponge.absorb(input);
+
+        Self::new(sponge)
+    }
+
+    pub fn bytes_per_element() -> usize {
+        (E::Fr::CAPACITY / 8) as usize
+    }
+
+    pub fn squeeze(
+        &mut self
+    ) -> E::Fr {
+        self.buffer.truncate(0);
+        self.position = 0;
+
+        self.sponge.squeeze_out_single()
+    }
+
+    pub fn squeeze_into(
+        &mut self,
+        into: &mut [E::Fr]
+    ) {
+        for el in into.iter_mut() {
+            *el = self.squeeze();
+        }
+    }
+
+    pub fn read_bytes(
+        &mut self,
+        into: &mut [u8]
+    ) {
+        let mut filled = 0;
+        while filled < into.len() {
+            if self.position == self.buffer.len() {
+                let mut repr = self.sponge.squeeze_out_single().into_repr();
+                while repr >= self.byte_output_bound {
+                    repr = self.sponge.squeeze_out_single().into_repr();
+                }
+                self.buffer.truncate(0);
+                repr.write_le(&mut self.buffer).unwrap();
+                self.buffer.truncate(Self::bytes_per_element());
+                self.position = 0;
+            }
+
+            let available = std::cmp::min(self.buffer.len() - self.position, into.len() - filled);
+            into[filled..(filled + available)].copy_from_slice(&self.buffer[self.position..(self.position + available)]);
+            self.position += available;
+            filled += available;
+        }
+    }
+}
+
+impl<'a, E: PoseidonEngine> std::io::Read for PoseidonXof<'a, E> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.read_bytes(buf);
+
+        Ok(buf.len())
+    }
+}
+
+impl<'a, E: PoseidonEngine> Rng for PoseidonXof<'a, E> {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        self.read_bytes(&mut buf);
+
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.read_bytes(&mut buf);
+
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.read_bytes(dest);
+    }
+}
+
+/// Deterministic PRNG seeded by field elements that are absorbed under given params
+impl<'a, 'b, E: PoseidonEngine> SeedableRng<(&'a E::Params, &'b [E::Fr])> for PoseidonXof<'a, E> {
+    fn reseed(&mut self, seed: (&'a E::Params, &'b [E::Fr])) {
+        *self = Self::from_seed(seed);
+    }
+
+    fn from_seed(seed: (&'a E::Params, &'b [E::Fr])) -> Self {
+        let (params, input) = seed;
+
+        Self::from_input(params, input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{Rng, SeedableRng, thread_rng};
+    use std::io::Read;
+    use crate::pairing::bn256::{Bn256, Fr};
+    use crate::pairing::ff::Field;
+    use crate::bn256::Bn256PoseidonParams;
+    use crate::group_hash::BlakeHasher;
+    use crate::{poseidon_hash, PoseidonHashParams};
+    use super::*;
+
+    #[test]
+    fn test_bn256_xof_elements() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let input: Vec<Fr> = (0..params.rate()).map(|_| rng.gen()).collect();
+
+        let mut sponge = StatefulSponge::<Bn256>::new(&params);
+        sponge.absorb(&input);
+        let expected: Vec<Fr> = (0..7).map(|_| sponge.squeeze_out_single()).collect();
+
+        let mut reader = PoseidonXof::<Bn256>::from_input(&params, &input);
+        let mut output = vec![Fr::zero(); 7];
+        reader.squeeze_into(&mut output);
+
+        assert_eq!(output, expected);
+        assert_eq!(output[0], poseidon_hash::<Bn256>(&params, &input)[0]);
+    }
+
+    #[test]
+    fn test_bn256_xof_bytes() {
+        let rng = &mut thread_rng();
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let input: Vec<Fr> = (0..3).map(|_| rng.gen()).collect();
+
+        let mut reader = PoseidonXof::<Bn256>::from_input(&params, &input);
+        let mut expected = [0u8; 100];
+        reader.read_exact(&mut expected).unwrap();
+
+        let mut reader = PoseidonXof::<Bn256>::from_input(&params, &input);
+        let mut output = vec![];
+        for len in [1, 30, 2, 67].iter() {
+            let mut buf = vec![0u8; *len];
+            reader.read_exact(&mut buf).unwrap();
+            output.extend(buf);
+        }
+        assert_eq!(&output[..], &expected[..]);
+
+        // bytes come from the first element that is not rejected
+        let mut reader = PoseidonXof::<Bn256>::from_input(&params, &input);
+        let mut first = reader.squeeze().into_repr();
+        while first >= reader.byte_output_bound {
+            first = reader.squeeze().into_repr();
+        }
+        let mut first_bytes = vec![];
+        first.write_le(&mut first_bytes).unwrap();
+        assert_eq!(&first_bytes[..31], &expected[..31]);
+    }
+
+    #[test]
+    fn test_bn256_xof_rejects_biased_elements() {
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let reader = PoseidonXof::<Bn256>::from_input(&params, &[]);
+        // 48 * 2^248 is the largest multiple of 2^248 below the characteristic
+        let mut bound = <Fr as PrimeField>::Repr::from(48);
+        bound.shl(248);
+        assert_eq!(reader.byte_output_bound, bound);
+
+        // find a seed whose first squeezed element is rejected
+        let (seed, rejected) = (0u64..)
+            .map(|i| {
+                let seed = [Fr::from_repr(<Fr as PrimeField>::Repr::from(i)).unwrap()];
+                let first = PoseidonXof::<Bn256>::from_seed((&params, &seed[..])).squeeze();
+                (seed, first)
+            })
+            .find(|(_, first)| first.into_repr() >= bound)
+            .unwrap();
+
+        let mut reader = PoseidonXof::<Bn256>::from_seed((&params, &seed[..]));
+        let mut bytes = [0u8; 31];
+        reader.read_exact(&mut bytes).unwrap();
+
+        let mut elements = PoseidonXof::<Bn256>::from_seed((&params, &seed[..]));
+        assert_eq!(elements.squeeze(), rejected);
+        let mut expected = vec![];
+        elements.squeeze().into_repr().write_le(&mut expected).unwrap();
+        assert_eq!(&bytes[..], &expected[..31]);
+    }
+
+    #[test]
+    fn test_bn256_xof_rng() {
+        let params = Bn256PoseidonParams::new_2_into_1::<BlakeHasher>();
+        let seed = [Fr::one(), Fr::zero()];
+
+        let mut a = PoseidonXof::<Bn256>::from_seed((&params, &seed[..]));
+        let mut b = PoseidonXof::<Bn256>::from_seed((&params, &seed[..]));
+        let a_values: Vec<Fr> = (0..4).map(|_| a.gen()).collect();
+        let b_values: Vec<Fr> = (0..4).map(|_| b.gen()).collect();
+        assert_eq!(a_values, b_values);
+
+        b.reseed((&params, &seed[..1]));
+        let other: Vec<Fr> = (0..4).map(|_| b.gen()).collect();
+        assert!(other != a_values);
+    }
+}